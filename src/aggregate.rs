@@ -0,0 +1,78 @@
+//! Rolls the process list up into one row per user, command or owner.
+
+use std::collections::HashMap;
+
+use crate::snapshot::ProcessInfo;
+
+/// What processes are grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    User,
+    /// the executable name, e.g. every `postgres` worker together
+    Command,
+    /// the systemd unit, container or pod, see [`crate::cgroup::Owner`]
+    Owner,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 3] = [GroupBy::User, GroupBy::Command, GroupBy::Owner];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GroupBy::User => "User",
+            GroupBy::Command => "Command",
+            GroupBy::Owner => "Owner",
+        }
+    }
+
+    /// The group a process belongs to.
+    pub fn key(&self, proc: &ProcessInfo) -> String {
+        match self {
+            GroupBy::User => match (&proc.user, proc.uid) {
+                (Some(user), _) => user.clone(),
+                (None, Some(uid)) => uid.to_string(),
+                (None, None) => "?".to_string(),
+            },
+            GroupBy::Command => proc.name.clone(),
+            GroupBy::Owner => proc.owner.as_ref().map_or_else(|| "-".to_string(), |owner| owner.label()),
+        }
+    }
+}
+
+/// Sums over the processes of one group.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessGroup {
+    pub key: String,
+    pub processes: usize,
+    pub threads: usize,
+    /// raw CPU usage in percent, like [`ProcessInfo::cpu_usage`]
+    pub cpu_usage: f32,
+    /// resident memory in bytes; pages shared between members count once per member
+    pub memory: u64,
+    pub disk_read_rate: u64,
+    pub disk_write_rate: u64,
+    /// members in the order they were given
+    pub pids: Vec<u32>,
+}
+
+/// Groups `processes` by `by`, ordered by key. Threads that sysinfo lists as
+/// processes are skipped: their process already accounts for them.
+pub fn aggregate<'a>(processes: impl IntoIterator<Item = &'a ProcessInfo>, by: GroupBy) -> Vec<ProcessGroup> {
+    let mut groups: HashMap<String, ProcessGroup> = HashMap::new();
+
+    for proc in processes.into_iter().filter(|proc| !proc.is_thread) {
+        let key = by.key(proc);
+        let group = groups.entry(key.clone()).or_insert_with(|| ProcessGroup { key, ..Default::default() });
+        group.processes += 1;
+        group.threads += proc.thread_count;
+        group.cpu_usage += proc.cpu_usage;
+        group.memory += proc.memory;
+        group.disk_read_rate += proc.disk_read_rate;
+        group.disk_write_rate += proc.disk_write_rate;
+        group.pids.push(proc.pid);
+    }
+
+    let mut groups: Vec<ProcessGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| a.key.cmp(&b.key));
+    groups
+}
@@ -0,0 +1,335 @@
+//! The cgroup v2 hierarchy: finding it, walking it, reading each group's
+//! controller files and telling from a path which unit or container owns it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the cgroup v2 hierarchy is mounted: `/sys/fs/cgroup` on unified
+/// systems, `/sys/fs/cgroup/unified` on hybrid ones. `None` without cgroup v2.
+pub fn mount_point() -> Option<PathBuf> {
+    ["/sys/fs/cgroup", "/sys/fs/cgroup/unified"]
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.join("cgroup.controllers").exists())
+}
+
+/// The cgroup v2 path of a process, e.g. `/system.slice/sshd.service`, from
+/// the `0::` line of `/proc/<pid>/cgroup`.
+pub fn process_cgroup(pid: u32) -> Option<String> {
+    let content = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.to_string())
+}
+
+/// Whether the cgroup at `path` is `ancestor` or lies below it.
+pub fn is_within(path: &str, ancestor: &str) -> bool {
+    match path.strip_prefix(ancestor) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || ancestor.ends_with('/'),
+        None => false,
+    }
+}
+
+/// Container runtimes recognised in cgroup paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Runtime {
+    Docker,
+    Containerd,
+    Podman,
+}
+
+impl Runtime {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Runtime::Docker => "docker",
+            Runtime::Containerd => "containerd",
+            Runtime::Podman => "podman",
+        }
+    }
+}
+
+/// What a process belongs to, as far as its cgroup path tells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Owner {
+    /// a Kubernetes pod by UID; its containers all count as the pod
+    Pod(String),
+    /// a container by full ID
+    Container(Runtime, String),
+    /// a systemd service, scope or slice, e.g. `sshd.service`
+    Unit(String),
+}
+
+impl Owner {
+    /// Parses the owner out of a cgroup path. The pod wins over its
+    /// containers, a container over the unit it runs in, and the deepest
+    /// unit over the slices above it. No daemon is asked; only the naming
+    /// schemes of systemd and of the runtimes' cgroup drivers are known.
+    pub fn from_path(path: &str) -> Option<Owner> {
+        let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+        if let Some(uid) = components.iter().find_map(|c| pod_uid(c)) {
+            return Some(Owner::Pod(uid));
+        }
+        let container = components.iter().enumerate().rev().find_map(|(idx, component)| {
+            let parent = idx.checked_sub(1).map(|idx| components[idx]);
+            container_id(component, parent)
+        });
+        if let Some((runtime, id)) = container {
+            return Some(Owner::Container(runtime, id));
+        }
+        components
+            .iter()
+            .rev()
+            .find(|c| [".service", ".scope", ".slice"].iter().any(|suffix| c.ends_with(suffix)))
+            .map(|unit| Owner::Unit(unit.to_string()))
+    }
+
+    /// A short label: the unit name, `docker:0123456789ab` with the ID cut to
+    /// 12 characters like `docker ps`, or `pod:<uid>`.
+    pub fn label(&self) -> String {
+        match self {
+            Owner::Pod(uid) => format!("pod:{}", uid),
+            Owner::Container(runtime, id) => format!("{}:{}", runtime.name(), &id[..id.len().min(12)]),
+            Owner::Unit(unit) => unit.clone(),
+        }
+    }
+}
+
+/// The pod UID in `kubepods-burstable-pod<uid>.slice` (systemd driver, with
+/// the UID's dashes turned into underscores) or `pod<uid>` (cgroupfs driver).
+fn pod_uid(component: &str) -> Option<String> {
+    let name = component.strip_suffix(".slice").unwrap_or(component);
+    let (_, uid) = name.rsplit_once("pod")?;
+    let uid = uid.replace('_', "-");
+    let is_uid = uid.len() == 36 && uid.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    is_uid.then_some(uid)
+}
+
+/// The runtime and ID of a container scope: `docker-<id>.scope`,
+/// `cri-containerd-<id>.scope` and `libpod-<id>.scope` with the systemd
+/// driver, or a bare `<id>` below `docker` or `libpod_parent` with cgroupfs.
+fn container_id(component: &str, parent: Option<&str>) -> Option<(Runtime, String)> {
+    let is_id = |id: &str| id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit());
+
+    if let Some(scope) = component.strip_suffix(".scope") {
+        let prefixes = [
+            ("docker-", Runtime::Docker),
+            ("cri-containerd-", Runtime::Containerd),
+            ("libpod-", Runtime::Podman),
+        ];
+        return prefixes.iter().find_map(|(prefix, runtime)| {
+            let id = scope.strip_prefix(prefix)?;
+            is_id(id).then(|| (*runtime, id.to_string()))
+        });
+    }
+    let runtime = match parent {
+        Some("docker") => Runtime::Docker,
+        Some("libpod_parent") => Runtime::Podman,
+        _ => return None,
+    };
+    is_id(component).then(|| (runtime, component.to_string()))
+}
+
+/// Pressure stall averages over the last 10 seconds, in percent of wall time
+/// some task was stalled on the resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pressure {
+    pub cpu: Option<f32>,
+    pub memory: Option<f32>,
+    pub io: Option<f32>,
+}
+
+/// The counters of one cgroup as read. Files of controllers that are not
+/// enabled for the group are missing, so their values are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct CgroupStat {
+    /// path below the mount point, `/` for the root
+    pub path: String,
+    /// 0 for the root
+    pub depth: usize,
+    /// `usage_usec` of `cpu.stat`
+    pub cpu_usage_usec: Option<u64>,
+    /// `memory.current` in bytes
+    pub memory_current: Option<u64>,
+    /// `memory.max` in bytes, `None` when it is `max` or missing
+    pub memory_max: Option<u64>,
+    /// `rbytes` and `wbytes` of `io.stat`, summed over devices
+    pub io_read_bytes: Option<u64>,
+    pub io_written_bytes: Option<u64>,
+    /// `pids.current`: tasks in the group and its descendants
+    pub pids_current: Option<u64>,
+    pub pressure: Pressure,
+    /// processes directly in the group, from `cgroup.procs`
+    pub procs: usize,
+}
+
+/// Reads every cgroup below `root` depth-first, parents before their children
+/// and siblings by name.
+pub fn walk(root: &Path) -> Vec<CgroupStat> {
+    let mut stats = Vec::new();
+    walk_into(root, "/", 0, &mut stats);
+    stats
+}
+
+fn walk_into(dir: &Path, path: &str, depth: usize, stats: &mut Vec<CgroupStat>) {
+    stats.push(read_stat(dir, path, depth));
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    children.sort();
+    for child in children {
+        let child_path = if depth == 0 {
+            format!("/{}", child)
+        } else {
+            format!("{}/{}", path, child)
+        };
+        walk_into(&dir.join(&child), &child_path, depth + 1, stats);
+    }
+}
+
+fn read_stat(dir: &Path, path: &str, depth: usize) -> CgroupStat {
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    let number = |file: &str| read(file).and_then(|s| s.trim().parse().ok());
+
+    let (io_read_bytes, io_written_bytes) = match read("io.stat") {
+        Some(stat) => (Some(io_sum(&stat, "rbytes")), Some(io_sum(&stat, "wbytes"))),
+        None => (None, None),
+    };
+
+    CgroupStat {
+        path: path.to_string(),
+        depth,
+        cpu_usage_usec: read("cpu.stat").and_then(|stat| {
+            stat.lines()
+                .find_map(|line| line.strip_prefix("usage_usec "))
+                .and_then(|value| value.trim().parse().ok())
+        }),
+        memory_current: number("memory.current"),
+        memory_max: number("memory.max"),
+        io_read_bytes,
+        io_written_bytes,
+        pids_current: number("pids.current"),
+        pressure: Pressure {
+            cpu: read("cpu.pressure").and_then(|s| some_avg10(&s)),
+            memory: read("memory.pressure").and_then(|s| some_avg10(&s)),
+            io: read("io.pressure").and_then(|s| some_avg10(&s)),
+        },
+        procs: read("cgroup.procs").map_or(0, |procs| procs.lines().count()),
+    }
+}
+
+/// Sums one key over the per-device lines of `io.stat`
+/// (`8:0 rbytes=1024 wbytes=0 rios=1 ...`).
+fn io_sum(stat: &str, key: &str) -> u64 {
+    stat.split_whitespace()
+        .filter_map(|field| field.strip_prefix(key)?.strip_prefix('='))
+        .filter_map(|value| value.parse::<u64>().ok())
+        .sum()
+}
+
+/// `avg10` of the `some` line of a pressure file
+/// (`some avg10=0.12 avg60=0.05 avg300=0.01 total=1234`).
+fn some_avg10(pressure: &str) -> Option<f32> {
+    pressure
+        .lines()
+        .find(|line| line.starts_with("some "))?
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f4e1c2b9a8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f";
+    const POD_UID: &str = "0f1d2c3b-4a59-6e7f-8091-a2b3c4d5e6f7";
+
+    fn container(runtime: Runtime) -> Option<Owner> {
+        Some(Owner::Container(runtime, ID.to_string()))
+    }
+
+    #[test]
+    fn docker_scope() {
+        let path = format!("/system.slice/docker-{}.scope", ID);
+        assert_eq!(Owner::from_path(&path), container(Runtime::Docker));
+    }
+
+    #[test]
+    fn docker_cgroupfs() {
+        assert_eq!(Owner::from_path(&format!("/docker/{}", ID)), container(Runtime::Docker));
+    }
+
+    #[test]
+    fn kubepods_systemd_pod_wins_over_its_container() {
+        let path = format!(
+            "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod{}.slice/cri-containerd-{}.scope",
+            POD_UID.replace('-', "_"),
+            ID
+        );
+        assert_eq!(Owner::from_path(&path), Some(Owner::Pod(POD_UID.to_string())));
+    }
+
+    #[test]
+    fn kubepods_cgroupfs() {
+        let path = format!("/kubepods/besteffort/pod{}/{}", POD_UID, ID);
+        assert_eq!(Owner::from_path(&path), Some(Owner::Pod(POD_UID.to_string())));
+    }
+
+    #[test]
+    fn containerd_scope() {
+        let path = format!("/system.slice/cri-containerd-{}.scope", ID);
+        assert_eq!(Owner::from_path(&path), container(Runtime::Containerd));
+    }
+
+    #[test]
+    fn rootless_podman_container_wins_over_its_units() {
+        let path = format!("/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container", ID);
+        assert_eq!(Owner::from_path(&path), container(Runtime::Podman));
+    }
+
+    #[test]
+    fn session_scope() {
+        let path = "/user.slice/user-1000.slice/session-2.scope";
+        assert_eq!(Owner::from_path(path), Some(Owner::Unit("session-2.scope".to_string())));
+    }
+
+    #[test]
+    fn deepest_unit_wins() {
+        let path = "/user.slice/user-1000.slice/user@1000.service/app.slice/foo.service";
+        assert_eq!(Owner::from_path(path), Some(Owner::Unit("foo.service".to_string())));
+        assert_eq!(Owner::from_path("/system.slice"), Some(Owner::Unit("system.slice".to_string())));
+    }
+
+    #[test]
+    fn no_owner() {
+        assert_eq!(Owner::from_path("/"), None);
+        assert_eq!(Owner::from_path("/demo/leaf"), None);
+        // too short to be a container ID
+        assert_eq!(Owner::from_path("/docker/abc123"), None);
+        assert_eq!(Owner::from_path("/kubepods"), None);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(container(Runtime::Docker).unwrap().label(), "docker:3f4e1c2b9a8d");
+        assert_eq!(Owner::Pod(POD_UID.to_string()).label(), format!("pod:{}", POD_UID));
+        assert_eq!(Owner::Unit("sshd.service".to_string()).label(), "sshd.service");
+    }
+
+    #[test]
+    fn within() {
+        assert!(is_within("/system.slice/sshd.service", "/system.slice"));
+        assert!(is_within("/system.slice", "/system.slice"));
+        assert!(is_within("/system.slice", "/"));
+        assert!(!is_within("/system.slice-other", "/system.slice"));
+    }
+}
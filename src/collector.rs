@@ -0,0 +1,495 @@
+//! Reads sysinfo and `/proc` and turns them into [`Snapshot`]s.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use procfs::process::Process;
+use procfs::ProcResult;
+use sysinfo::{Disks, System, ThreadKind, Users};
+
+use crate::cgroup;
+use crate::sched;
+use crate::snapshot::{
+    CgroupInfo, CpuInfo, DiskInfo, MemoryStats, ProcessInfo, Snapshot, SystemInfo, ThreadInfo,
+};
+
+struct ThreadSample {
+    last_cpu_time: u64, // utime + stime
+    last_seen: Instant,
+}
+
+struct DiskSample {
+    start_time: u64, // tells a reused PID apart from the process sampled before
+    read_bytes: u64,
+    written_bytes: u64,
+    last_seen: Instant,
+}
+
+/// `smaps_rollup` walks the page tables of the process, so its figures are
+/// reused for this long before being read again.
+const ROLLUP_MAX_AGE: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy)]
+struct RollupSample {
+    start_time: u64,
+    pss: Option<u64>,
+    uss: Option<u64>,
+    read_at: Instant,
+}
+
+struct CgroupSample {
+    cpu_usage_usec: Option<u64>,
+    io_read_bytes: Option<u64>,
+    io_written_bytes: Option<u64>,
+    last_seen: Instant,
+}
+
+/// Owns the sysinfo handles and the samples needed to turn cumulative
+/// counters into rates. Call [`Collector::refresh`] once per interval.
+pub struct Collector {
+    sys: System,
+    disks: Disks,
+    users: Users,
+    thread_pid: Option<u32>,
+    thread_samples: HashMap<i32, ThreadSample>,
+    disk_samples: HashMap<u32, DiskSample>,
+    io_priorities: bool,
+    affinities: bool,
+    proportional_memory: bool,
+    rollup_samples: HashMap<u32, RollupSample>,
+    cgroup_paths: bool,
+    cgroups: bool,
+    cgroup_samples: HashMap<String, CgroupSample>,
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new_all(),
+            disks: Disks::new_with_refreshed_list(),
+            users: Users::new_with_refreshed_list(),
+            thread_pid: None,
+            thread_samples: HashMap::new(),
+            disk_samples: HashMap::new(),
+            io_priorities: false,
+            affinities: false,
+            proportional_memory: false,
+            rollup_samples: HashMap::new(),
+            cgroup_paths: false,
+            cgroups: false,
+            cgroup_samples: HashMap::new(),
+        }
+    }
+
+    /// Selects the process whose threads are included in each snapshot.
+    pub fn set_thread_pid(&mut self, pid: Option<u32>) {
+        if self.thread_pid != pid {
+            self.thread_pid = pid;
+            self.thread_samples.clear();
+        }
+    }
+
+    pub fn thread_pid(&self) -> Option<u32> {
+        self.thread_pid
+    }
+
+    /// Includes I/O priorities in each snapshot; otherwise they are `None`.
+    /// That is one syscall per process, so only enable this while they are shown.
+    pub fn set_io_priorities(&mut self, enabled: bool) {
+        self.io_priorities = enabled;
+    }
+
+    /// Includes CPU affinities in each snapshot; otherwise they are `None`.
+    pub fn set_affinities(&mut self, enabled: bool) {
+        self.affinities = enabled;
+    }
+
+    /// Includes PSS and USS in each snapshot. They are expensive to read,
+    /// so only enable this while something shows them.
+    pub fn set_proportional_memory(&mut self, enabled: bool) {
+        if self.proportional_memory != enabled {
+            self.proportional_memory = enabled;
+            self.rollup_samples.clear();
+        }
+    }
+
+    /// Includes each process's cgroup path and owner in each snapshot. That
+    /// reads one more file per process, so only enable this while they are used.
+    pub fn set_cgroup_paths(&mut self, enabled: bool) {
+        self.cgroup_paths = enabled;
+    }
+
+    /// Includes the cgroup v2 hierarchy in each snapshot. Walking it reads
+    /// several files per group, so only enable this while it is shown.
+    pub fn set_cgroups(&mut self, enabled: bool) {
+        if self.cgroups != enabled {
+            self.cgroups = enabled;
+            self.cgroup_samples.clear();
+        }
+    }
+
+    /// Refreshes every source and returns a new snapshot.
+    pub fn refresh(&mut self) -> Snapshot {
+        self.sys.refresh_all();
+        self.disks.refresh(true);
+        self.users.refresh();
+
+        Snapshot {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            system: system_info(),
+            cpus: self
+                .sys
+                .cpus()
+                .iter()
+                .map(|cpu| CpuInfo {
+                    name: cpu.name().to_string(),
+                    usage: cpu.cpu_usage(),
+                    frequency: cpu.frequency(),
+                })
+                .collect(),
+            global_cpu_usage: self.sys.global_cpu_usage(),
+            memory: self.memory_stats(),
+            disks: self
+                .disks
+                .iter()
+                .map(|disk| DiskInfo {
+                    name: disk.name().to_string_lossy().into_owned(),
+                    mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                    total_space: disk.total_space(),
+                    available_space: disk.available_space(),
+                })
+                .collect(),
+            processes: self.processes(),
+            thread_pid: self.thread_pid,
+            threads: self.collect_threads(),
+            cgroups: self.collect_cgroups(),
+        }
+    }
+
+    /// Walks the cgroup hierarchy without refreshing anything else, so the
+    /// cgroup browser can show it as soon as it opens. Empty unless enabled.
+    pub fn collect_cgroups(&mut self) -> Vec<CgroupInfo> {
+        if !self.cgroups {
+            return Vec::new();
+        }
+        let Some(root) = cgroup::mount_point() else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        let mut cgroup_samples = HashMap::new();
+
+        let cgroups = cgroup::walk(&root)
+            .into_iter()
+            .map(|stat| {
+                let prev = self.cgroup_samples.get(&stat.path);
+                let elapsed = prev.map_or(0.0, |prev| now.duration_since(prev.last_seen).as_secs_f64());
+                // per second since the previous refresh of the same group
+                let rate = |current: Option<u64>, previous: fn(&CgroupSample) -> Option<u64>| {
+                    match (current, prev.and_then(previous)) {
+                        (Some(current), Some(previous)) if elapsed > 0.0 => {
+                            Some(current.saturating_sub(previous) as f64 / elapsed)
+                        }
+                        (Some(_), _) => Some(0.0),
+                        (None, _) => None,
+                    }
+                };
+                let info = CgroupInfo {
+                    path: stat.path.clone(),
+                    depth: stat.depth,
+                    cpu_usage: rate(stat.cpu_usage_usec, |s| s.cpu_usage_usec).map(|usec| (usec / 1e4) as f32),
+                    memory_current: stat.memory_current,
+                    memory_max: stat.memory_max,
+                    io_read_rate: rate(stat.io_read_bytes, |s| s.io_read_bytes).map(|rate| rate as u64),
+                    io_write_rate: rate(stat.io_written_bytes, |s| s.io_written_bytes).map(|rate| rate as u64),
+                    pids_current: stat.pids_current,
+                    pressure: stat.pressure,
+                    procs: stat.procs,
+                };
+                cgroup_samples.insert(stat.path, CgroupSample {
+                    cpu_usage_usec: stat.cpu_usage_usec,
+                    io_read_bytes: stat.io_read_bytes,
+                    io_written_bytes: stat.io_written_bytes,
+                    last_seen: now,
+                });
+                info
+            })
+            .collect();
+
+        // dropping the old map forgets groups that were removed
+        self.cgroup_samples = cgroup_samples;
+        cgroups
+    }
+
+    /// Samples the threads of the selected process without refreshing anything
+    /// else, so the thread panel can update as soon as the selection changes.
+    pub fn collect_threads(&mut self) -> Vec<ThreadInfo> {
+        match self.thread_pid {
+            Some(pid) => self.thread_info(pid).unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    fn processes(&mut self) -> Vec<ProcessInfo> {
+        let now = Instant::now();
+        let mut disk_samples = HashMap::with_capacity(self.disk_samples.len());
+        let mut rollup_samples = HashMap::with_capacity(self.rollup_samples.len());
+
+        let mut processes: Vec<ProcessInfo> = self
+            .sys
+            .processes()
+            .iter()
+            .map(|(pid, proc)| {
+                let proc_fs = Process::new(pid.as_u32() as i32).ok();
+                let stat = proc_fs.as_ref().and_then(|p| p.stat().ok());
+                let status = proc_fs.as_ref().and_then(|p| p.status().ok());
+                let disk_usage = proc.disk_usage();
+
+                // bytes per second since the previous refresh of the same process
+                let (disk_read_rate, disk_write_rate) = match self.disk_samples.get(&pid.as_u32()) {
+                    Some(prev) if prev.start_time == proc.start_time() => {
+                        let elapsed = now.duration_since(prev.last_seen).as_secs_f64();
+                        if elapsed > 0.0 {
+                            (
+                                (disk_usage.total_read_bytes.saturating_sub(prev.read_bytes) as f64 / elapsed) as u64,
+                                (disk_usage.total_written_bytes.saturating_sub(prev.written_bytes) as f64 / elapsed) as u64,
+                            )
+                        } else {
+                            (0, 0)
+                        }
+                    }
+                    _ => (0, 0),
+                };
+                disk_samples.insert(pid.as_u32(), DiskSample {
+                    start_time: proc.start_time(),
+                    read_bytes: disk_usage.total_read_bytes,
+                    written_bytes: disk_usage.total_written_bytes,
+                    last_seen: now,
+                });
+                // PSS and USS, re-read once the cached figures are too old; the age
+                // is spread by PID so the processes do not all expire at once
+                let (pss, uss) = if self.proportional_memory {
+                    let max_age = ROLLUP_MAX_AGE + Duration::from_millis(pid.as_u32() as u64 % 1000 * 5);
+                    let sample = match self.rollup_samples.get(&pid.as_u32()) {
+                        Some(prev) if prev.start_time == proc.start_time() && prev.read_at.elapsed() < max_age => *prev,
+                        _ => {
+                            let (pss, uss) = proc_fs.as_ref().map(read_rollup).unwrap_or_default();
+                            RollupSample { start_time: proc.start_time(), pss, uss, read_at: now }
+                        }
+                    };
+                    let figures = (sample.pss, sample.uss);
+                    rollup_samples.insert(pid.as_u32(), sample);
+                    figures
+                } else {
+                    (None, None)
+                };
+                let cgroup = if self.cgroup_paths {
+                    cgroup::process_cgroup(pid.as_u32())
+                } else {
+                    None
+                };
+                let cmd: Vec<String> = proc.cmd().iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+
+                ProcessInfo {
+                    pid: pid.as_u32(),
+                    ppid: proc.parent().map(|p| p.as_u32()),
+                    name: proc.name().to_string_lossy().into_owned(),
+                    cmd: cmd.join(" "),
+                    uid: proc.user_id().map(|uid| **uid),
+                    user: proc
+                        .user_id()
+                        .and_then(|uid| self.users.get_user_by_id(uid))
+                        .map(|user| user.name().to_string()),
+                    nice: stat.as_ref().map(|s| s.nice).unwrap_or_default(),
+                    priority: stat.as_ref().map(|s| s.priority).unwrap_or_default(),
+                    status: proc.status().to_string(),
+                    state: stat.as_ref().map(|s| s.state).unwrap_or('?'),
+                    cpu_usage: proc.cpu_usage(),
+                    memory: proc.memory(),
+                    virtual_memory: proc.virtual_memory(),
+                    // Status reports kB, like top's SHR = RssFile + RssShmem
+                    shared_memory: status
+                        .as_ref()
+                        .map(|s| (s.rssfile.unwrap_or(0) + s.rssshmem.unwrap_or(0)) * 1024)
+                        .unwrap_or_default(),
+                    tty: stat.as_ref().and_then(|s| tty_name(s.tty_nr())),
+                    session: stat.as_ref().map(|s| s.session).unwrap_or_default(),
+                    pgrp: stat.as_ref().map(|s| s.pgrp).unwrap_or_default(),
+                    start_time: proc.start_time(),
+                    cpu_time: proc.accumulated_cpu_time(),
+                    disk_read_bytes: disk_usage.total_read_bytes,
+                    disk_written_bytes: disk_usage.total_written_bytes,
+                    disk_read_rate,
+                    disk_write_rate,
+                    pss,
+                    uss,
+                    swap: status.as_ref().and_then(|s| s.vmswap).unwrap_or_default() * 1024,
+                    thread_count: stat.as_ref().map(|s| s.num_threads as usize).unwrap_or_default(),
+                    io_priority: if self.io_priorities {
+                        sched::get_io_priority(pid.as_u32()).ok()
+                    } else {
+                        None
+                    },
+                    affinity: if self.affinities {
+                        sched::get_affinity(pid.as_u32()).ok()
+                    } else {
+                        None
+                    },
+                    last_cpu: stat.as_ref().and_then(|s| s.processor),
+                    owner: cgroup.as_deref().and_then(cgroup::Owner::from_path),
+                    cgroup,
+                    is_thread: proc.thread_kind() == Some(ThreadKind::Userland),
+                }
+            })
+            .collect();
+
+        // dropping the old maps forgets processes that have exited
+        self.disk_samples = disk_samples;
+        self.rollup_samples = rollup_samples;
+
+        processes.sort_by_key(|p| p.pid);
+        processes
+    }
+
+    fn memory_stats(&self) -> MemoryStats {
+        let (total, used, available, cached, free) = btop_memory_stats();
+        MemoryStats {
+            total,
+            used,
+            available,
+            cached,
+            free,
+            swap_total: self.sys.total_swap(),
+            swap_used: self.sys.used_swap(),
+        }
+    }
+
+    fn thread_info(&mut self, pid: u32) -> ProcResult<Vec<ThreadInfo>> {
+        let process = Process::new(pid as i32)?;
+        let tasks = process.tasks()?;
+        let now = Instant::now();
+        let clock_ticks = procfs::ticks_per_second() as f64;
+
+        tasks
+            .map(|task_result| {
+                let task = task_result?; // Propagates task enumeration errors
+                let stat = task.stat()?; // Propagates stat parsing errors
+                let total_cpu = stat.utime + stat.stime;
+
+                let mut cpu_percent = 0.0;
+
+                if let Some(prev) = self.thread_samples.get(&task.tid) {
+                    let elapsed = now.duration_since(prev.last_seen).as_secs_f64();
+                    if elapsed > 0.0 {
+                        let delta_cpu = total_cpu.saturating_sub(prev.last_cpu_time) as f64 / clock_ticks;
+                        cpu_percent = 100.0 * (delta_cpu / elapsed);
+                    }
+                }
+
+                self.thread_samples.insert(task.tid, ThreadSample {
+                    last_cpu_time: total_cpu,
+                    last_seen: now,
+                });
+
+                Ok(ThreadInfo {
+                    tid: task.tid as u32,
+                    name: stat.comm,
+                    state: stat.state.to_string(),
+                    cpu: cpu_percent,
+                    priority: stat.priority,
+                })
+            })
+            .collect()
+    }
+}
+
+/// PSS and USS (private clean + dirty) in bytes from `smaps_rollup`; `None`
+/// for kernel threads and processes we may not inspect.
+fn read_rollup(process: &Process) -> (Option<u64>, Option<u64>) {
+    let Ok(rollup) = process.smaps_rollup() else {
+        return (None, None);
+    };
+    let Some(map) = rollup.memory_map_rollup.0.first() else {
+        return (None, None);
+    };
+    let value = |key: &str| map.extension.map.get(key).copied();
+    let uss = match (value("Private_Clean"), value("Private_Dirty")) {
+        (Some(clean), Some(dirty)) => Some(clean + dirty),
+        _ => None,
+    };
+    (value("Pss"), uss)
+}
+
+/// Names a terminal from its device numbers the way ps does (see devices.txt).
+fn tty_name((major, minor): (i32, i32)) -> Option<String> {
+    match major {
+        0 => None,
+        4 if minor < 64 => Some(format!("tty{}", minor)),
+        4 => Some(format!("ttyS{}", minor - 64)),
+        136..=143 => Some(format!("pts/{}", (major - 136) * 256 + minor)),
+        _ => Some(format!("{}:{}", major, minor)),
+    }
+}
+
+fn system_info() -> SystemInfo {
+    SystemInfo {
+        name: System::name().unwrap_or("Unknown".to_string()),
+        kernel_version: System::kernel_version().unwrap_or("Unknown".to_string()),
+        os_version: System::os_version().unwrap_or("Unknown".to_string()),
+        host_name: System::host_name().unwrap_or("Unknown".to_string()),
+        uptime: System::uptime(),
+    }
+}
+
+/// Returns (total, used, available, cached, free) in bytes, calculated exactly like btop
+fn btop_memory_stats() -> (u64, u64, u64, u64, u64) {
+    let mut memtotal = 0;
+    let mut memfree = 0;
+    let mut memavailable = 0;
+    let mut cached = 0;
+    let mut sreclaimable = 0;
+    let mut shmem = 0;
+
+    let Ok(file) = File::open("/proc/meminfo") else {
+        return (0, 0, 0, 0, 0);
+    };
+    let reader = BufReader::new(file);
+
+    for line in reader.lines().map_while(Result::ok) {
+        let mut fields = line.split_whitespace();
+        let key = fields.next().unwrap_or_default();
+        let value = fields.next().and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+        match key {
+            "MemTotal:" => memtotal = value,
+            "MemFree:" => memfree = value,
+            "MemAvailable:" => memavailable = value,
+            "Cached:" => cached = value,
+            "SReclaimable:" => sreclaimable = value,
+            "Shmem:" => shmem = value,
+            _ => {}
+        }
+    }
+
+    // btop's formulas:
+    let used = memtotal.saturating_sub(memavailable);
+    let cached_btop = (cached + sreclaimable).saturating_sub(shmem);
+
+    // /proc/meminfo is in KiB
+    (
+        memtotal * 1024,
+        used * 1024,
+        memavailable * 1024,
+        cached_btop * 1024,
+        memfree * 1024,
+    )
+}
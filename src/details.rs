@@ -0,0 +1,438 @@
+//! Everything `/proc/<pid>` says about one process, read on demand for the
+//! detail view rather than for every process on every refresh.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use procfs::net::{TcpState, UdpState};
+use procfs::process::{FDTarget, Io, Limit, Limits, MMapPath, MemoryMap, Namespace, Process, Status};
+use procfs::{ProcResult, ProcessCGroup};
+
+/// Details of one process. Each part is read separately, so a permission
+/// error on one (e.g. `environ` of another user's process) leaves the others.
+#[derive(Debug)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    /// clock ticks after boot, to tell the process from a later one with its PID
+    pub start_time: u64,
+    pub status: ProcResult<Status>,
+    pub cmdline: ProcResult<Vec<String>>,
+    /// environment variables sorted by name
+    pub environ: ProcResult<Vec<(String, String)>>,
+    pub cwd: ProcResult<PathBuf>,
+    pub exe: ProcResult<PathBuf>,
+    pub root: ProcResult<PathBuf>,
+    pub limits: ProcResult<Limits>,
+    pub cgroups: ProcResult<Vec<ProcessCGroup>>,
+    /// namespaces sorted by type
+    pub namespaces: ProcResult<Vec<Namespace>>,
+    pub oom_score: ProcResult<u16>,
+    pub oom_score_adj: ProcResult<i16>,
+    pub io: ProcResult<Io>,
+    /// open file descriptors in fd order
+    pub open_files: ProcResult<Vec<OpenFile>>,
+}
+
+/// What an open file descriptor refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Socket,
+    Pipe,
+    AnonInode,
+    MemFd,
+    Other,
+}
+
+impl FileKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::File => "file",
+            FileKind::Socket => "socket",
+            FileKind::Pipe => "pipe",
+            FileKind::AnonInode => "anon",
+            FileKind::MemFd => "memfd",
+            FileKind::Other => "other",
+        }
+    }
+}
+
+/// One entry of `/proc/<pid>/fd`, with its `fdinfo`.
+#[derive(Debug, Clone)]
+pub struct OpenFile {
+    pub fd: i32,
+    pub kind: FileKind,
+    /// path, resolved socket, `pipe:[inode]` or the anon inode's name
+    pub target: String,
+    /// `open(2)` flags from `fdinfo`, `None` when it could not be read
+    pub flags: Option<i32>,
+    /// file offset from `fdinfo`
+    pub pos: Option<u64>,
+}
+
+impl ProcessDetails {
+    /// Reads the details of `pid`; fails only when the process is gone.
+    pub fn read(pid: u32) -> ProcResult<Self> {
+        let process = Process::new(pid as i32)?;
+        let start_time = process.stat()?.starttime;
+
+        let environ = process.environ().map(|environ| {
+            let mut vars: Vec<(String, String)> = environ
+                .into_iter()
+                .map(|(name, value)| (name.to_string_lossy().into_owned(), value.to_string_lossy().into_owned()))
+                .collect();
+            vars.sort();
+            vars
+        });
+        let namespaces = process.namespaces().map(|namespaces| {
+            let mut namespaces: Vec<Namespace> = namespaces.0.into_values().collect();
+            namespaces.sort_by(|a, b| a.ns_type.cmp(&b.ns_type));
+            namespaces
+        });
+
+        Ok(Self {
+            pid,
+            start_time,
+            status: process.status(),
+            cmdline: process.cmdline(),
+            environ,
+            cwd: process.cwd(),
+            exe: process.exe(),
+            root: process.root(),
+            limits: process.limits(),
+            cgroups: process.cgroups().map(|cgroups| cgroups.0),
+            namespaces,
+            oom_score: process.oom_score(),
+            oom_score_adj: process.oom_score_adj(),
+            io: process.io(),
+            open_files: open_files(&process),
+        })
+    }
+}
+
+/// What backs a memory mapping, to tell heap growth from mapped files and
+/// shared libraries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MapCategory {
+    /// `[heap]` and anonymous mappings (malloc arenas, mmap'd buffers)
+    Heap,
+    Stack,
+    /// files with `.so` in their name
+    Library,
+    File,
+    /// `[vdso]`, `[vvar]`, System V shared memory and the like
+    Other,
+}
+
+impl MapCategory {
+    pub const ALL: [MapCategory; 5] = [
+        MapCategory::Heap,
+        MapCategory::Stack,
+        MapCategory::Library,
+        MapCategory::File,
+        MapCategory::Other,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapCategory::Heap => "Heap & anonymous",
+            MapCategory::Stack => "Stack",
+            MapCategory::Library => "Shared libraries",
+            MapCategory::File => "Mapped files",
+            MapCategory::Other => "Other",
+        }
+    }
+}
+
+/// Memory figures of one mapping from `smaps`, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapUsage {
+    pub rss: u64,
+    pub pss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    pub swap: u64,
+}
+
+impl MapUsage {
+    pub fn add(&mut self, other: &MapUsage) {
+        self.rss += other.rss;
+        self.pss += other.pss;
+        self.shared_clean += other.shared_clean;
+        self.shared_dirty += other.shared_dirty;
+        self.private_clean += other.private_clean;
+        self.private_dirty += other.private_dirty;
+        self.swap += other.swap;
+    }
+}
+
+/// One mapping of `/proc/<pid>/smaps`.
+#[derive(Debug, Clone)]
+pub struct MemoryMapping {
+    pub start: u64,
+    pub end: u64,
+    /// e.g. `r-xp`
+    pub perms: String,
+    /// the backing file or a pseudo path such as `[heap]` or `[anon]`
+    pub path: String,
+    pub category: MapCategory,
+    pub usage: MapUsage,
+}
+
+/// Reads `/proc/<pid>/smaps`. This walks the page tables of the process, so
+/// it is read only while the memory map is on screen.
+pub fn memory_maps(pid: u32) -> ProcResult<Vec<MemoryMapping>> {
+    let maps = Process::new(pid as i32)?.smaps()?;
+    Ok(maps.into_iter().map(memory_mapping).collect())
+}
+
+fn memory_mapping(map: MemoryMap) -> MemoryMapping {
+    let (path, category) = match map.pathname {
+        MMapPath::Path(path) => {
+            let path = path.display().to_string();
+            let category = if path.contains(".so") { MapCategory::Library } else { MapCategory::File };
+            (path, category)
+        }
+        MMapPath::Heap => ("[heap]".to_string(), MapCategory::Heap),
+        MMapPath::Anonymous => ("[anon]".to_string(), MapCategory::Heap),
+        MMapPath::Stack => ("[stack]".to_string(), MapCategory::Stack),
+        MMapPath::TStack(tid) => (format!("[stack:{}]", tid), MapCategory::Stack),
+        MMapPath::Vdso => ("[vdso]".to_string(), MapCategory::Other),
+        MMapPath::Vvar => ("[vvar]".to_string(), MapCategory::Other),
+        MMapPath::Vsyscall => ("[vsyscall]".to_string(), MapCategory::Other),
+        MMapPath::Rollup => ("[rollup]".to_string(), MapCategory::Other),
+        MMapPath::Vsys(key) => (format!("/SYSV{:08x}", key), MapCategory::Other),
+        MMapPath::Other(name) => (name, MapCategory::Other),
+    };
+    // procfs already converted the kB figures to bytes
+    let value = |key: &str| map.extension.map.get(key).copied().unwrap_or(0);
+    let usage = MapUsage {
+        rss: value("Rss"),
+        pss: value("Pss"),
+        shared_clean: value("Shared_Clean"),
+        shared_dirty: value("Shared_Dirty"),
+        private_clean: value("Private_Clean"),
+        private_dirty: value("Private_Dirty"),
+        swap: value("Swap"),
+    };
+
+    MemoryMapping {
+        start: map.address.0,
+        end: map.address.1,
+        perms: map.perms.as_str(),
+        path,
+        category,
+        usage,
+    }
+}
+
+/// Lists the open fds of a process, resolving socket inodes against the
+/// process's own `/proc/<pid>/net` tables so network namespaces are honoured.
+fn open_files(process: &Process) -> ProcResult<Vec<OpenFile>> {
+    let mut sockets: Option<HashMap<u64, String>> = None;
+    let mut files = Vec::new();
+
+    for fd in process.fd()? {
+        // the fd may be closed between listing and reading it
+        let Ok(fd) = fd else {
+            continue;
+        };
+        let (kind, target) = match fd.target {
+            FDTarget::Path(path) => (FileKind::File, path.display().to_string()),
+            FDTarget::Socket(inode) => {
+                let sockets = sockets.get_or_insert_with(|| socket_table(process));
+                let target = sockets
+                    .get(&inode)
+                    .cloned()
+                    .unwrap_or_else(|| format!("socket:[{}]", inode));
+                (FileKind::Socket, target)
+            }
+            // a network namespace handle, not a socket
+            FDTarget::Net(inode) => (FileKind::Other, format!("net:[{}]", inode)),
+            FDTarget::Pipe(inode) => (FileKind::Pipe, format!("pipe:[{}]", inode)),
+            FDTarget::AnonInode(name) => (FileKind::AnonInode, format!("anon_inode:{}", name)),
+            FDTarget::MemFD(name) => (FileKind::MemFd, format!("memfd:{}", name)),
+            FDTarget::Other(name, inode) => (FileKind::Other, format!("{}:[{}]", name, inode)),
+        };
+        let (flags, pos) = fdinfo(process.pid(), fd.fd);
+        files.push(OpenFile { fd: fd.fd, kind, target, flags, pos });
+    }
+
+    files.sort_by_key(|file| file.fd);
+    Ok(files)
+}
+
+/// Reads `flags` (octal) and `pos` from `/proc/<pid>/fdinfo/<fd>`.
+fn fdinfo(pid: i32, fd: i32) -> (Option<i32>, Option<u64>) {
+    let Ok(info) = fs::read_to_string(format!("/proc/{}/fdinfo/{}", pid, fd)) else {
+        return (None, None);
+    };
+    let mut flags = None;
+    let mut pos = None;
+    for line in info.lines() {
+        if let Some(value) = line.strip_prefix("flags:") {
+            flags = i32::from_str_radix(value.trim(), 8).ok();
+        } else if let Some(value) = line.strip_prefix("pos:") {
+            pos = value.trim().parse().ok();
+        }
+    }
+    (flags, pos)
+}
+
+/// Describes every TCP, UDP and Unix socket by inode, like
+/// `TCP 127.0.0.1:5432 -> 127.0.0.1:40112 (ESTABLISHED)`.
+fn socket_table(process: &Process) -> HashMap<u64, String> {
+    let mut table = HashMap::new();
+
+    let tcp = process.tcp().into_iter().flatten().chain(process.tcp6().into_iter().flatten());
+    for entry in tcp {
+        let description = match entry.state {
+            TcpState::Listen => format!("TCP {} (LISTEN)", entry.local_address),
+            state => format!("TCP {} -> {} ({})", entry.local_address, entry.remote_address, tcp_state_name(&state)),
+        };
+        table.insert(entry.inode, description);
+    }
+    let udp = process.udp().into_iter().flatten().chain(process.udp6().into_iter().flatten());
+    for entry in udp {
+        let description = match entry.state {
+            UdpState::Established => format!("UDP {} -> {}", entry.local_address, entry.remote_address),
+            _ => format!("UDP {}", entry.local_address),
+        };
+        table.insert(entry.inode, description);
+    }
+    for entry in process.unix().into_iter().flatten() {
+        let path = entry
+            .path
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "(unnamed)".to_string());
+        table.insert(entry.inode, format!("UNIX {} ({:?})", path, entry.state));
+    }
+
+    table
+}
+
+fn tcp_state_name(state: &TcpState) -> &'static str {
+    match state {
+        TcpState::Established => "ESTABLISHED",
+        TcpState::SynSent => "SYN_SENT",
+        TcpState::SynRecv => "SYN_RECV",
+        TcpState::FinWait1 => "FIN_WAIT1",
+        TcpState::FinWait2 => "FIN_WAIT2",
+        TcpState::TimeWait => "TIME_WAIT",
+        TcpState::Close => "CLOSE",
+        TcpState::CloseWait => "CLOSE_WAIT",
+        TcpState::LastAck => "LAST_ACK",
+        TcpState::Listen => "LISTEN",
+        TcpState::Closing => "CLOSING",
+        TcpState::NewSynRecv => "NEW_SYN_RECV",
+    }
+}
+
+/// Names the access mode and the interesting status flags of an fd, e.g.
+/// `O_RDWR|O_NONBLOCK|O_CLOEXEC`.
+pub fn flag_names(flags: i32) -> String {
+    let mut names = vec![match flags & libc::O_ACCMODE {
+        libc::O_RDONLY => "O_RDONLY",
+        libc::O_WRONLY => "O_WRONLY",
+        _ => "O_RDWR",
+    }];
+    let known = [
+        (libc::O_APPEND, "O_APPEND"),
+        (libc::O_NONBLOCK, "O_NONBLOCK"),
+        (libc::O_CLOEXEC, "O_CLOEXEC"),
+        (libc::O_DIRECT, "O_DIRECT"),
+        (libc::O_DSYNC, "O_DSYNC"),
+        (libc::O_NOATIME, "O_NOATIME"),
+        (libc::O_PATH, "O_PATH"),
+    ];
+    names.extend(known.iter().filter(|(flag, _)| flags & flag == *flag).map(|(_, name)| *name));
+    // O_SYNC includes the O_DSYNC bit
+    if flags & libc::O_SYNC == libc::O_SYNC {
+        names.retain(|name| *name != "O_DSYNC");
+        names.push("O_SYNC");
+    }
+    names.join("|")
+}
+
+/// The limits in the order and with the names of `/proc/<pid>/limits`,
+/// each with its unit (empty when it is a count).
+pub fn limit_rows(limits: &Limits) -> [(&'static str, &Limit, &'static str); 16] {
+    [
+        ("Max cpu time", &limits.max_cpu_time, "seconds"),
+        ("Max file size", &limits.max_file_size, "bytes"),
+        ("Max data size", &limits.max_data_size, "bytes"),
+        ("Max stack size", &limits.max_stack_size, "bytes"),
+        ("Max core file size", &limits.max_core_file_size, "bytes"),
+        ("Max resident set", &limits.max_resident_set, "bytes"),
+        ("Max processes", &limits.max_processes, "processes"),
+        ("Max open files", &limits.max_open_files, "files"),
+        ("Max locked memory", &limits.max_locked_memory, "bytes"),
+        ("Max address space", &limits.max_address_space, "bytes"),
+        ("Max file locks", &limits.max_file_locks, "locks"),
+        ("Max pending signals", &limits.max_pending_signals, "signals"),
+        ("Max msgqueue size", &limits.max_msgqueue_size, "bytes"),
+        ("Max nice priority", &limits.max_nice_priority, ""),
+        ("Max realtime priority", &limits.max_realtime_priority, ""),
+        ("Max realtime timeout", &limits.max_realtime_timeout, "us"),
+    ]
+}
+
+/// Capability names by bit number, from `capability.h`.
+const CAPABILITIES: [&str; 41] = [
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_DAC_READ_SEARCH",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETPCAP",
+    "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_BROADCAST",
+    "CAP_NET_ADMIN",
+    "CAP_NET_RAW",
+    "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER",
+    "CAP_SYS_MODULE",
+    "CAP_SYS_RAWIO",
+    "CAP_SYS_CHROOT",
+    "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT",
+    "CAP_SYS_ADMIN",
+    "CAP_SYS_BOOT",
+    "CAP_SYS_NICE",
+    "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME",
+    "CAP_SYS_TTY_CONFIG",
+    "CAP_MKNOD",
+    "CAP_LEASE",
+    "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL",
+    "CAP_SETFCAP",
+    "CAP_MAC_OVERRIDE",
+    "CAP_MAC_ADMIN",
+    "CAP_SYSLOG",
+    "CAP_WAKE_ALARM",
+    "CAP_BLOCK_SUSPEND",
+    "CAP_AUDIT_READ",
+    "CAP_PERFMON",
+    "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+/// Names the capabilities set in a mask from `/proc/<pid>/status`; bits the
+/// table does not know yet are shown by number.
+pub fn capability_names(mask: u64) -> Vec<String> {
+    (0..64)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| match CAPABILITIES.get(bit as usize) {
+            Some(name) => name.to_string(),
+            None => format!("cap_{}", bit),
+        })
+        .collect()
+}
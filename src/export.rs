@@ -0,0 +1,137 @@
+//! Machine-readable output of snapshots, used by `--batch` mode.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::snapshot::{ProcessInfo, Snapshot};
+
+/// Output format for [`BatchWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// one JSON object per process per line
+    Json,
+    /// a header row followed by one row per process
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => Err(format!("unknown format '{}' (expected json or csv)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// The process list columns, with raw values instead of the
+/// human-readable strings the TUI shows.
+#[derive(Serialize)]
+struct ProcessRecord<'a> {
+    /// milliseconds since the epoch, shared by every row of one snapshot
+    timestamp: u64,
+    pid: u32,
+    name: &'a str,
+    nice: i64,
+    priority: i64,
+    state: &'a str,
+    cpu_usage: f32,
+    /// bytes
+    memory: u64,
+    /// seconds since the epoch
+    start_time: u64,
+    /// milliseconds
+    cpu_time: u64,
+    /// bytes
+    disk_read: u64,
+    /// bytes
+    disk_write: u64,
+    /// bytes per second
+    disk_read_rate: u64,
+    /// bytes per second
+    disk_write_rate: u64,
+    threads: usize,
+}
+
+impl<'a> ProcessRecord<'a> {
+    fn new(timestamp: u64, proc: &'a ProcessInfo) -> Self {
+        Self {
+            timestamp,
+            pid: proc.pid,
+            name: &proc.name,
+            nice: proc.nice,
+            priority: proc.priority,
+            state: &proc.status,
+            cpu_usage: proc.cpu_usage,
+            memory: proc.memory,
+            start_time: proc.start_time,
+            cpu_time: proc.cpu_time,
+            disk_read: proc.disk_read_bytes,
+            disk_write: proc.disk_written_bytes,
+            disk_read_rate: proc.disk_read_rate,
+            disk_write_rate: proc.disk_write_rate,
+            threads: proc.thread_count,
+        }
+    }
+}
+
+enum Sink<W: Write> {
+    Json(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+/// Writes the processes of successive snapshots to `W`. CSV output gets a
+/// single header row; JSON output is one object per line.
+pub struct BatchWriter<W: Write> {
+    sink: Sink<W>,
+}
+
+impl<W: Write> BatchWriter<W> {
+    pub fn new(out: W, format: Format) -> Self {
+        let sink = match format {
+            Format::Json => Sink::Json(out),
+            Format::Csv => Sink::Csv(Box::new(csv::Writer::from_writer(out))),
+        };
+        Self { sink }
+    }
+
+    /// Writes every process in `snapshot` and flushes the output.
+    pub fn write_snapshot(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        for proc in &snapshot.processes {
+            let record = ProcessRecord::new(snapshot.timestamp, proc);
+            match &mut self.sink {
+                Sink::Json(out) => {
+                    serde_json::to_writer(&mut *out, &record)?;
+                    out.write_all(b"\n")?;
+                }
+                Sink::Csv(out) => out.serialize(&record).map_err(csv_to_io)?,
+            }
+        }
+
+        match &mut self.sink {
+            Sink::Json(out) => out.flush(),
+            Sink::Csv(out) => out.flush(),
+        }
+    }
+}
+
+fn csv_to_io(err: csv::Error) -> io::Error {
+    match err.into_kind() {
+        csv::ErrorKind::Io(err) => err,
+        other => io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", other)),
+    }
+}
@@ -0,0 +1,325 @@
+//! Process filters typed at the `/` search prompt.
+//!
+//! A query is a whitespace-separated list of terms and a process is shown
+//! when every term matches:
+//!
+//! - `word` — name or command line contains `word` (case-insensitive)
+//! - `re:pattern` — name or command line matches the regular expression
+//! - `user:name` — owned by `name` (or by a numeric UID)
+//! - `state:D` — one-letter state is one of the given letters, e.g. `state:DR`
+//! - `pid:1,42` — PID is one of the list
+//! - `owner:docker` — systemd unit, container or pod label contains the text
+//!   (case-insensitive), e.g. `owner:sshd.service` or `owner:pod:`
+//! - `cpu>10`, `mem>=1G`, `threads>100`, `nice<0`, `ppid=1` — numeric
+//!   comparisons with `>`, `>=`, `<`, `<=` or `=`; `mem` takes K/M/G/T suffixes
+
+use regex::{Regex, RegexBuilder};
+
+use crate::snapshot::ProcessInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Cpu,
+    Mem,
+    Threads,
+    Nice,
+    Pid,
+    Ppid,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cpu" => Some(Field::Cpu),
+            "mem" | "memory" => Some(Field::Mem),
+            "threads" => Some(Field::Threads),
+            "nice" | "ni" => Some(Field::Nice),
+            "pid" => Some(Field::Pid),
+            "ppid" => Some(Field::Ppid),
+            _ => None,
+        }
+    }
+
+    fn value(&self, proc: &ProcessInfo) -> Option<f64> {
+        match self {
+            Field::Cpu => Some(proc.cpu_usage as f64),
+            Field::Mem => Some(proc.memory as f64),
+            Field::Threads => Some(proc.thread_count as f64),
+            Field::Nice => Some(proc.nice as f64),
+            Field::Pid => Some(proc.pid as f64),
+            Field::Ppid => proc.ppid.map(|p| p as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+impl Op {
+    fn apply(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Op::Gt => lhs > rhs,
+            Op::Ge => lhs >= rhs,
+            Op::Lt => lhs < rhs,
+            Op::Le => lhs <= rhs,
+            Op::Eq => lhs == rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Text(String),
+    Regex(Regex),
+    User(String),
+    State(Vec<char>),
+    Pids(Vec<u32>),
+    Owner(String),
+    Compare(Field, Op, f64),
+}
+
+impl Term {
+    fn parse(term: &str) -> Result<Self, String> {
+        if let Some((key, value)) = term.split_once(':') {
+            match key.to_ascii_lowercase().as_str() {
+                "re" => {
+                    return RegexBuilder::new(value)
+                        .case_insensitive(true)
+                        .build()
+                        .map(Term::Regex)
+                        .map_err(|e| format!("bad regex: {}", e));
+                }
+                "user" => return Ok(Term::User(value.to_string())),
+                "owner" | "unit" => return Ok(Term::Owner(value.to_lowercase())),
+                "state" | "s" => {
+                    return Ok(Term::State(value.chars().map(|c| c.to_ascii_uppercase()).collect()));
+                }
+                "pid" => {
+                    return value
+                        .split(',')
+                        .map(|p| p.trim().parse::<u32>().map_err(|_| format!("bad PID '{}'", p)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(Term::Pids);
+                }
+                _ => {}
+            }
+        }
+
+        // two-character operators first so `>=` is not read as `>`
+        for (token, op) in [(">=", Op::Ge), ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt), ("=", Op::Eq)] {
+            if let Some((name, value)) = term.split_once(token) {
+                if let Some(field) = Field::from_name(name) {
+                    let value = parse_number(field, value)
+                        .ok_or_else(|| format!("bad number '{}' for {}", value, name))?;
+                    return Ok(Term::Compare(field, op, value));
+                }
+            }
+        }
+
+        Ok(Term::Text(term.to_lowercase()))
+    }
+
+    fn matches(&self, proc: &ProcessInfo) -> bool {
+        match self {
+            Term::Text(text) => {
+                proc.name.to_lowercase().contains(text) || proc.cmd.to_lowercase().contains(text)
+            }
+            Term::Regex(re) => re.is_match(&proc.name) || re.is_match(&proc.cmd),
+            Term::User(user) => {
+                proc.user.as_deref() == Some(user.as_str())
+                    || proc.uid.is_some_and(|uid| uid.to_string() == *user)
+            }
+            Term::State(states) => states.contains(&proc.state),
+            Term::Pids(pids) => pids.contains(&proc.pid),
+            Term::Owner(text) => proc
+                .owner
+                .as_ref()
+                .is_some_and(|owner| owner.label().to_lowercase().contains(text)),
+            Term::Compare(field, op, rhs) => field.value(proc).is_some_and(|lhs| op.apply(lhs, *rhs)),
+        }
+    }
+}
+
+// memory values accept K/M/G/T suffixes (powers of 1024)
+fn parse_number(field: Field, value: &str) -> Option<f64> {
+    let value = value.trim_end_matches('%');
+    if field == Field::Mem {
+        let upper = value.to_ascii_uppercase();
+        let digits = upper.trim_end_matches(['B', 'I']);
+        let (digits, multiplier) = match digits.chars().last() {
+            Some('K') => (&digits[..digits.len() - 1], 1024.0),
+            Some('M') => (&digits[..digits.len() - 1], 1024.0 * 1024.0),
+            Some('G') => (&digits[..digits.len() - 1], 1024.0 * 1024.0 * 1024.0),
+            Some('T') => (&digits[..digits.len() - 1], 1024.0 * 1024.0 * 1024.0 * 1024.0),
+            _ => (digits, 1.0),
+        };
+        return digits.parse::<f64>().ok().map(|n| n * multiplier);
+    }
+    value.parse::<f64>().ok()
+}
+
+/// A parsed search query. An empty query matches every process.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessFilter {
+    query: String,
+    terms: Vec<Term>,
+}
+
+impl ProcessFilter {
+    /// Parses `query`, returning a message describing the first bad term.
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let terms = query
+            .split_whitespace()
+            .map(Term::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            query: query.trim().to_string(),
+            terms,
+        })
+    }
+
+    /// The query this filter was parsed from.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Whether a term looks at [`ProcessInfo::owner`], which is only
+    /// collected on request.
+    pub fn uses_owner(&self) -> bool {
+        self.terms.iter().any(|term| matches!(term, Term::Owner(_)))
+    }
+
+    pub fn matches(&self, proc: &ProcessInfo) -> bool {
+        self.terms.iter().all(|term| term.matches(proc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process() -> ProcessInfo {
+        ProcessInfo {
+            pid: 42,
+            ppid: Some(1),
+            name: "postgres".to_string(),
+            cmd: "/usr/lib/postgresql/bin/postgres -D /var/lib/data".to_string(),
+            uid: Some(999),
+            user: Some("postgres".to_string()),
+            nice: -5,
+            state: 'S',
+            cpu_usage: 12.5,
+            memory: 1536 * 1024 * 1024,
+            thread_count: 8,
+            ..Default::default()
+        }
+    }
+
+    fn matches(query: &str) -> bool {
+        ProcessFilter::parse(query).unwrap().matches(&process())
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let filter = ProcessFilter::parse("   ").unwrap();
+        assert!(filter.is_empty());
+        assert!(filter.matches(&process()));
+    }
+
+    #[test]
+    fn text_searches_name_and_command_case_insensitively() {
+        assert!(matches("POSTGRES"));
+        assert!(matches("/var/lib"));
+        assert!(!matches("mysql"));
+    }
+
+    #[test]
+    fn every_term_must_match() {
+        assert!(matches("postgres user:postgres"));
+        assert!(!matches("postgres user:root"));
+    }
+
+    #[test]
+    fn regex() {
+        assert!(matches("re:^post.*s$"));
+        assert!(!matches("re:^gres"));
+        assert!(ProcessFilter::parse("re:(").is_err());
+    }
+
+    #[test]
+    fn user_by_name_or_uid() {
+        assert!(matches("user:postgres"));
+        assert!(matches("user:999"));
+        assert!(!matches("user:0"));
+    }
+
+    #[test]
+    fn state_letters() {
+        assert!(matches("state:DS"));
+        assert!(matches("s:s"));
+        assert!(!matches("state:R"));
+    }
+
+    #[test]
+    fn pid_list() {
+        assert!(matches("pid:1,42"));
+        assert!(!matches("pid:7"));
+        assert!(ProcessFilter::parse("pid:1,x").is_err());
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(matches("cpu>10"));
+        assert!(matches("cpu>=12.5"));
+        assert!(!matches("cpu<12.5"));
+        assert!(matches("cpu<=12.5%"));
+        assert!(matches("threads=8"));
+        assert!(matches("nice<0"));
+        assert!(matches("ppid=1"));
+        assert!(ProcessFilter::parse("cpu>lots").is_err());
+    }
+
+    #[test]
+    fn memory_suffixes() {
+        assert!(matches("mem>1G"));
+        assert!(matches("mem>=1.5G"));
+        assert!(!matches("mem>1.5G"));
+        assert!(matches("mem<2GiB"));
+        assert!(matches("memory=1536M"));
+        assert!(matches("mem>1024k"));
+    }
+
+    #[test]
+    fn unknown_comparison_falls_back_to_text() {
+        assert!(!matches("a=b"));
+        let mut proc = process();
+        proc.cmd.push_str(" --opt=b");
+        assert!(ProcessFilter::parse("opt=b").unwrap().matches(&proc));
+    }
+
+    #[test]
+    fn owner() {
+        let filter = ProcessFilter::parse("owner:SSHD").unwrap();
+        assert!(filter.uses_owner());
+        let mut proc = process();
+        assert!(!filter.matches(&proc));
+        proc.owner = Some(crate::cgroup::Owner::Unit("sshd.service".to_string()));
+        assert!(filter.matches(&proc));
+        assert!(!ProcessFilter::parse("postgres").unwrap().uses_owner());
+    }
+
+    #[test]
+    fn query_is_kept_trimmed() {
+        assert_eq!(ProcessFilter::parse("  cpu>1  ").unwrap().query(), "cpu>1");
+    }
+}
@@ -0,0 +1,25 @@
+//! Data collection for the Linux task manager.
+//!
+//! The [`Collector`] reads sysinfo and `/proc` and produces a [`Snapshot`]:
+//! a plain, owned copy of everything the panels draw (processes, threads,
+//! memory, disks and CPUs). The TUI only renders snapshots, so other tools
+//! can reuse the same numbers without a terminal. [`sched`] reads and
+//! changes scheduling attributes of processes and threads, and [`details`]
+//! reads everything `/proc` has on a single process. [`aggregate`] sums the
+//! process list per user or per command, and [`cgroup`] walks the cgroup v2
+//! hierarchy.
+
+pub mod aggregate;
+pub mod cgroup;
+pub mod collector;
+pub mod details;
+pub mod export;
+pub mod filter;
+pub mod sched;
+pub mod snapshot;
+
+pub use collector::Collector;
+pub use filter::ProcessFilter;
+pub use snapshot::{
+    CgroupInfo, CpuInfo, DiskInfo, MemoryStats, ProcessInfo, Snapshot, SystemInfo, ThreadInfo,
+};
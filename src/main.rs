@@ -1,21 +1,36 @@
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
-use sysinfo::{System, Pid};
 use std::io;
-use std::fs;
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton,
+    MouseEvent, MouseEventKind,
+};
 use ratatui::{
-    layout::*, style::{Color, Modifier, Style}, symbols, text::{Line, Span,Text}, widgets::*, Frame,
+    layout::*, style::{Color, Modifier, Style}, symbols, text::{Line, Span}, widgets::*, Frame,
     buffer::Buffer, widgets::{Block, Borders, Paragraph},
 };
 use std::path::Path;
-use procfs::{process::Process, ProcResult};
-use nix::sys::signal::{kill, Signal};
-use nix::unistd::Pid as NixPid;
+use nix::errno::Errno;
 use libc::{getpriority, PRIO_PROCESS, c_int, syscall, SYS_tgkill,setpriority};
-use std::collections::HashMap;
-use std::time::Instant;
 use chrono::{Local, TimeZone};
+use linux_task_manager::{CgroupInfo, Collector, MemoryStats, ProcessFilter, ProcessInfo, Snapshot, ThreadInfo};
+use linux_task_manager::aggregate::{self, GroupBy, ProcessGroup};
+use linux_task_manager::cgroup;
+use linux_task_manager::details::{self, ProcessDetails};
+use linux_task_manager::export::{BatchWriter, Format};
+use linux_task_manager::sched::{self, IoClass, IoPriority, Policy};
+use clap::{Parser, ValueEnum};
+use crossterm::execute;
+use std::time::{Duration, Instant};
+use std::path::PathBuf;
+
+mod tui;
+use tui::columns::{ColumnId, ColumnLayout};
+use tui::sort::{cycle, SortOrder, ThreadSortMode};
+use tui::config::Config;
+use tui::detail::{DetailTab, DetailView, MapSortKey};
+use tui::signals::{self, SignalEntry};
 
 
 // btop-like colors
@@ -38,26 +53,130 @@ const LIGHT_RED: Color = Color::Rgb(250, 120, 125);
 const RED: Color = Color::Rgb(244, 80, 80);   
 
 
+/// Command-line options
+#[derive(Parser, Debug)]
+#[command(version, about = "A btop-like task manager for Linux")]
+struct Args {
+    /// Print snapshots to stdout instead of starting the TUI (like `top -b`)
+    #[arg(short, long)]
+    batch: bool,
+
+    /// Number of snapshots to print in batch mode (runs until killed if omitted)
+    #[arg(short = 'n', long, requires = "batch")]
+    iterations: Option<u64>,
+
+    /// Seconds between refreshes
+    #[arg(short = 'd', long, default_value_t = 0.75, value_parser = parse_interval)]
+    interval: f64,
+
+    /// Batch output format: json (one object per line) or csv
+    #[arg(long, default_value_t = Format::Json, requires = "batch")]
+    format: Format,
+
+    /// Initial sort column of the process list
+    #[arg(short, long, value_enum, default_value_t = ColumnId::Cpu)]
+    sort: ColumnId,
+
+    /// Initial sort direction (defaults to the column's natural direction)
+    #[arg(long, value_enum)]
+    order: Option<SortOrder>,
+
+    /// Only show these PIDs in the process list, e.g. `1,42,1337`
+    #[arg(long, value_delimiter = ',')]
+    filter_pids: Vec<u32>,
+
+    /// View to start in
+    #[arg(long, value_enum, default_value_t = View::Process)]
+    view: View,
+
+    /// Start in the thread view of this process
+    #[arg(short, long, conflicts_with = "view")]
+    pid: Option<u32>,
+
+    /// Config file to read and save the layout to
+    /// [default: $XDG_CONFIG_HOME/linux-task-manager/config.toml]
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Send SIGTERM, SIGKILL and other terminating signals without asking first
+    #[arg(long)]
+    no_confirm: bool,
+
+    /// Draw the tree's branch guides with ASCII characters, like `pstree -A`
+    #[arg(long)]
+    ascii: bool,
+}
+
+/// Views that can be selected at startup
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+enum View {
+    Process,
+    Tree,
+    Thread,
+}
+
+fn parse_interval(s: &str) -> Result<f64, String> {
+    let secs: f64 = s.parse().map_err(|_| format!("'{}' is not a number of seconds", s))?;
+    if secs.is_finite() && secs > 0.0 {
+        Ok(secs)
+    } else {
+        Err("interval must be greater than 0".to_string())
+    }
+}
+
+// prints the process list as JSON lines or CSV until the iteration count is reached
+fn run_batch(args: &Args) -> io::Result<()> {
+    let interval = Duration::from_secs_f64(args.interval);
+    let mut collector = Collector::new();
+    let stdout = io::stdout();
+    let mut writer = BatchWriter::new(stdout.lock(), args.format);
+
+    // Give system time to collect baseline metrics
+    collector.refresh();
+    std::thread::sleep(interval);
+
+    let mut iteration = 0;
+    loop {
+        let snapshot = collector.refresh();
+        match writer.write_snapshot(&snapshot) {
+            // the reader went away (e.g. piped into head), which is not an error for us
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+
+        iteration += 1;
+        if args.iterations.is_some_and(|n| iteration >= n) {
+            return Ok(());
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+// identifies a process across refreshes; the start time tells a reused PID apart
+type TreeKey = (u32, u64);
+
 #[derive(Eq, PartialEq)]
 // struct representing each process and its information
 struct TreeProc {
     name: String,
     pid: u32,
     ppid: u32,
+    start_time: u64,
+    is_thread: bool, // a thread sysinfo lists as a child of its process
     displayed: bool,
     children: Vec<Rc<RefCell<TreeProc>>>,
-    selected: bool,
 }
 
 impl TreeProc {
-    fn new(name: String, pid: u32, ppid: u32) -> Rc<RefCell<Self>> {
+    fn new(name: String, pid: u32, ppid: u32, start_time: u64, is_thread: bool) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(TreeProc {
             name,
             pid,
             ppid,
+            start_time,
+            is_thread,
             displayed: false,
             children: Vec::new(),
-            selected: false,
         }))
     }
     
@@ -77,7 +196,7 @@ impl TreeProc {
     }
 
     // getter for the parent process id
-    fn get_ppid(&self) -> u32 {
+    fn _get_ppid(&self) -> u32 {
         self.ppid
     }
 
@@ -91,135 +210,303 @@ impl TreeProc {
         self.children.len()
     }
     
-    // setter for selected
-    fn set_selected(&mut self, val: bool){
-        self.selected = val;
+    // the key expand/collapse state, tags and the cursor are remembered by
+    fn get_key(&self) -> TreeKey {
+        (self.pid, self.start_time)
     }
-    
-    // getter for selected
-    fn get_selected(&self) -> bool {
-        self.selected
+}
+
+// builds the process tree in one pass over a PID index and returns its roots in PID
+// order: init, kthreadd, and every process whose parent is not in the snapshot
+// (outside our PID namespace, or already reaped)
+fn tree_create(snapshot: &Snapshot) -> Vec<Rc<RefCell<TreeProc>>> {
+    let nodes: HashMap<u32, Rc<RefCell<TreeProc>>> = snapshot.processes
+        .iter()
+        .map(|process| {
+            let parent_pid = process.ppid.unwrap_or(0);
+            (process.pid, TreeProc::new(process.name.clone(), process.pid, parent_pid, process.start_time, process.is_thread))
+        })
+        .collect();
+
+    // snapshot processes are sorted by PID, so children end up in PID order too
+    let mut roots = Vec::new();
+    for process in &snapshot.processes {
+        let node = &nodes[&process.pid];
+        let parent = process.ppid.filter(|ppid| *ppid != process.pid).and_then(|ppid| nodes.get(&ppid));
+        match parent {
+            Some(parent) => parent.borrow_mut().addchild(Rc::clone(node)),
+            None => roots.push(Rc::clone(node)),
+        }
     }
+
+    roots
 }
 
-// creates a vector of Treeprocs and fills the in the information about each process
-fn tree_create() -> Vec<Rc<RefCell<TreeProc>>> {
-    let mut system = System::new_all();
-    system.refresh_all();
+// the characters the tree's branch guides are drawn with
+struct TreeGuides {
+    branch: &'static str, // a child with more siblings below it
+    last: &'static str,   // the last child
+    line: &'static str,   // continues the line of an ancestor with more children
+    blank: &'static str,  // below an ancestor's last child
+}
 
-    // vector for all the processes in the system 
-    let mut processes_running: Vec<Rc<RefCell<TreeProc>>> = Vec::new();
+const UNICODE_GUIDES: TreeGuides = TreeGuides { branch: "├─ ", last: "└─ ", line: "│  ", blank: "   " };
+const ASCII_GUIDES: TreeGuides = TreeGuides { branch: "|- ", last: "`- ", line: "|  ", blank: "   " };
 
-    // fills the pid and ppid for each process
-    for (pid, process) in system.processes() {
-        let parent_pid = process.parent().unwrap_or(0.into());
-        let curr_proc = TreeProc::new(process.name().to_string_lossy().into_owned(),pid.as_u32(), parent_pid.as_u32());
-        processes_running.push(curr_proc);
-    }
+// one line of the tree table, flattened from the TreeProc hierarchy
+struct TreeRow {
+    node: Rc<RefCell<TreeProc>>,
+    guide: String, // pstree-style branch guides drawn before the name
+    has_children: bool,
+    collapsed: bool,
+    parent: Option<TreeKey>,
+}
 
-    // creates a reference to each child of a process and adds them all to the children vector for each process
-    for proc in &processes_running {
-        for proc_child in &processes_running {
-            if proc.borrow().get_pid() == proc_child.borrow().get_ppid() {
-                proc.borrow_mut().addchild(Rc::clone(proc_child));
-            }
+// flattens the visible part of the tree in display order, stopping at collapsed nodes
+fn tree_rows(
+    roots: &[Rc<RefCell<TreeProc>>],
+    collapsed: &HashSet<TreeKey>,
+    snapshot: &Snapshot,
+    filter: &ProcessFilter,
+    guides: &TreeGuides,
+) -> Vec<TreeRow> {
+    let visible = tree_matches(roots, snapshot, filter);
+    let layout = TreeLayout { collapsed, visible: visible.as_ref(), guides };
+    let mut rows = Vec::new();
+    for root in roots {
+        if layout.visible.is_none_or(|visible| visible.contains(&root.borrow().get_key())) {
+            tree_flatten(root, "", "", None, &layout, &mut rows);
         }
     }
+    rows
+}
 
-    processes_running
+// what tree_flatten needs besides the node: folds, filter matches and guide characters
+struct TreeLayout<'a> {
+    collapsed: &'a HashSet<TreeKey>,
+    visible: Option<&'a HashSet<TreeKey>>,
+    guides: &'a TreeGuides,
 }
 
-// find the root process which is the parents of all parents
-fn find_root(process_arr: &Vec<Rc<RefCell<TreeProc>>>) -> Option<usize> {
-    for (index, proc) in process_arr.iter().enumerate() {
-        // this process would have the parent id 0 so we look for it in the vector of processes
-        if proc.borrow().get_ppid() == 0 {
-            return Some(index);
-        }
+fn tree_flatten(
+    proc: &Rc<RefCell<TreeProc>>,
+    prefix: &str,
+    branch: &str,
+    parent: Option<TreeKey>,
+    layout: &TreeLayout,
+    rows: &mut Vec<TreeRow>,
+) {
+    let TreeLayout { collapsed, visible, guides } = *layout;
+    let curr_proc = proc.borrow();
+    // skip branches with nothing that matches the filter
+    let children: Vec<&Rc<RefCell<TreeProc>>> = curr_proc
+        .children
+        .iter()
+        .filter(|child| visible.is_none_or(|visible| visible.contains(&child.borrow().get_key())))
+        .collect();
+    let is_collapsed = !children.is_empty() && collapsed.contains(&curr_proc.get_key());
+
+    rows.push(TreeRow {
+        node: Rc::clone(proc),
+        guide: format!("{}{}", prefix, branch),
+        has_children: !children.is_empty(),
+        collapsed: is_collapsed,
+        parent,
+    });
+    if is_collapsed {
+        return;
+    }
+
+    // children continue the vertical line of this row unless it was the last sibling
+    let child_prefix = if branch == guides.branch {
+        format!("{}{}", prefix, guides.line)
+    } else if branch == guides.last {
+        format!("{}{}", prefix, guides.blank)
+    } else {
+        prefix.to_string()
+    };
+    for (idx, child) in children.iter().enumerate() {
+        let branch = if idx + 1 == children.len() { guides.last } else { guides.branch };
+        tree_flatten(child, &child_prefix, branch, Some(curr_proc.get_key()), layout, rows);
     }
-    None
 }
 
-// creates the tree display of processes 
-fn tree_display(
-    process_arr: Vec<Rc<RefCell<TreeProc>>>,  // Take ownership of the vector
-    indent: usize,
-    current: u32,
-    sel: bool,
-) -> Vec<Line<'static>> {
-    let mut tree_levels = Vec::new();
+// CPU and memory of a process and all of its descendants, shown on collapsed nodes;
+// threads are skipped since each one repeats the usage of its whole process
+fn subtree_usage(proc: &Rc<RefCell<TreeProc>>, snapshot: &Snapshot) -> (f32, u64) {
+    let curr_proc = proc.borrow();
+    let (mut cpu, mut memory) = snapshot
+        .process(curr_proc.get_pid())
+        .map_or((0.0, 0), |info| (info.cpu_usage, info.memory));
+    for child in curr_proc.get_children().iter().filter(|child| !child.borrow().is_thread) {
+        let (child_cpu, child_memory) = subtree_usage(child, snapshot);
+        cpu += child_cpu;
+        memory += child_memory;
+    }
+    (cpu, memory)
+}
 
-    // creates a line for each process
-    for proc in process_arr {  
-        let curr_proc = proc.borrow();
-        let prefix = "  ".repeat(indent);
+// every node of the tree by key
+fn tree_nodes(roots: &[Rc<RefCell<TreeProc>>]) -> HashMap<TreeKey, Rc<RefCell<TreeProc>>> {
+    let mut nodes = HashMap::new();
+    let mut pending: Vec<Rc<RefCell<TreeProc>>> = roots.to_vec();
+    while let Some(node) = pending.pop() {
+        pending.extend(node.borrow().children.iter().cloned());
+        let key = node.borrow().get_key();
+        nodes.insert(key, node);
+    }
+    nodes
+}
 
-        // checks which process line is selected and highlights it
-        let line = if current == curr_proc.get_pid() || curr_proc.get_selected() == true{
-            Line::from(vec![
-                Span::raw(prefix.clone()),
-                Span::styled(
-                    format!("{}", curr_proc.get_pid()),
-                    Style::default()
-                        .fg(TREE_COLOR)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ])
-        } 
-        // if not selected then it does not get highlighted
-        else {
-            Line::from(vec![Span::raw(format!("{} -->  ({}) {}", prefix,curr_proc.get_name(), curr_proc.get_pid()))])
-        };
+// appends `proc` and its descendants to `keys`, deepest first, skipping any already seen;
+// threads are left out, as signalling a TID would reach the whole process once per thread
+fn tree_postorder(proc: &Rc<RefCell<TreeProc>>, seen: &mut HashSet<TreeKey>, keys: &mut Vec<TreeKey>) {
+    let curr_proc = proc.borrow();
+    for child in &curr_proc.children {
+        tree_postorder(child, seen, keys);
+    }
+    if !curr_proc.is_thread && seen.insert(curr_proc.get_key()) {
+        keys.push(curr_proc.get_key());
+    }
+}
 
-        tree_levels.push(line);
+// processes shown in the tree: those matching the filter and all of their ancestors.
+// None when there is no filter and everything is shown.
+fn tree_matches(
+    roots: &[Rc<RefCell<TreeProc>>],
+    snapshot: &Snapshot,
+    filter: &ProcessFilter,
+) -> Option<HashSet<TreeKey>> {
+    if filter.is_empty() {
+        return None;
+    }
+    let mut visible = HashSet::new();
+    for root in roots {
+        tree_mark_visible(root, snapshot, filter, &mut visible);
+    }
+    Some(visible)
+}
 
-        
-        let children = curr_proc.get_children();
-        // recursively calls the function to display all the processes
-        let children_text = tree_display(children, indent + 1, current,sel);
-        tree_levels.extend(children_text);
+fn tree_mark_visible(
+    proc: &Rc<RefCell<TreeProc>>,
+    snapshot: &Snapshot,
+    filter: &ProcessFilter,
+    visible: &mut HashSet<TreeKey>,
+) -> bool {
+    let curr_proc = proc.borrow();
+    let mut shown = snapshot
+        .process(curr_proc.get_pid())
+        .is_some_and(|info| filter.matches(info));
+    // every child is visited so matches deeper down are all recorded
+    for child in &curr_proc.children {
+        shown |= tree_mark_visible(child, snapshot, filter, visible);
     }
+    if shown {
+        visible.insert(curr_proc.get_key());
+    }
+    shown
+}
+
+
+
+/// State of the `/` search prompt while it is open
+struct SearchPrompt {
+    input: String,
+    previous: ProcessFilter, // restored when the prompt is cancelled
+    error: Option<String>,
+}
+
+/// State of the column picker overlay while it is open
+struct ColumnPicker {
+    selected: usize,
+    error: Option<String>, // set when saving the config failed
+}
 
-    tree_levels
+/// What a signal or renice applies to, fixed when the action is chosen so that
+/// a refresh re-sorting the list cannot change it
+#[derive(Clone)]
+enum Target {
+    Processes(Vec<u32>),
+    Thread { pid: u32, tid: u32, name: String },
+    /// signalled with killpg(2); `pids` are the members at the time, for the preview
+    Group { pgid: u32, pids: Vec<u32> },
+    /// every member of the session, signalled one by one
+    Session { sid: u32, pids: Vec<u32> },
 }
 
-// does depth first search on each process and returns a vector of that order
-fn stack_proc(procs: &mut Vec<Rc<RefCell<TreeProc>>>, parent: &Rc<RefCell<TreeProc>> ) {
-    let proc = parent.borrow();
-    for child in &proc.get_children() {
-        procs.push(Rc::clone(&child));
-        stack_proc(procs,child)
+impl Target {
+    // the processes the signal will reach
+    fn pids(&self) -> Vec<u32> {
+        match self {
+            Target::Processes(pids) | Target::Group { pids, .. } | Target::Session { pids, .. } => pids.clone(),
+            Target::Thread { tid, .. } => vec![*tid],
+        }
     }
 }
 
+/// State of the signal menu while it is open
+struct SignalMenu {
+    signals: Vec<SignalEntry>,
+    selected: usize,
+    // the selected process, then its process group and session; Tab picks one
+    targets: Vec<Target>,
+    scope: usize,
+}
 
-/// Enum to define sorting modes for the process list
-#[derive(PartialEq, Debug)]
-enum SortMode {
-    Cpu,
-    Memory,
-    Pid,
+/// State of the exact nice value prompt in the status bar
+struct NicePrompt {
+    input: String,
+    prompt: String,
+    target: Target,
+    error: Option<String>,
 }
 
-#[derive(PartialEq, Clone)]
-enum Mode {
-    Proc,
-    Thread,
+/// State of the scheduling policy dialog while it is open
+struct SchedDialog {
+    target: Target,
+    current: (Policy, i32), // policy and RT priority when the dialog was opened
+    policy: Policy,
+    priority: i32, // used by the real-time policies, 1-99
 }
 
-#[derive(Debug, Default, Clone)]
-struct ThreadInfo {
-    tid: u32,
-    name: String,
-    state: String,
-    cpu: f64,
-    priority: i64,
+/// State of the I/O priority dialog while it is open
+struct IoDialog {
+    target: Target,
+    current: IoPriority,
+    nice: i64, // of the first target, to show what the `none` class means
+    class: IoClass,
+    level: i32, // used by the realtime and best-effort classes, 0-7
+}
+
+/// State of the CPU affinity editor while it is open
+struct AffinityEditor {
+    target: Target,
+    cpus: Vec<bool>, // one checkbox per CPU of the snapshot
+    selected: usize,
+    error: Option<String>,
+}
+
+/// A destructive signal waiting for y/n in the status bar
+struct PendingSignal {
+    target: Target,
+    signal: c_int,
+    prompt: String,
+}
 
+/// Result of the last action, shown in the status bar for a few seconds
+struct StatusMessage {
+    text: String,
+    error: bool,
+    since: Instant,
 }
 
-struct ThreadSample {
-    last_cpu_time: u64,  // utime + stime
-    last_seen: Instant,
+// how long a status message stays in the status bar
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(PartialEq, Clone)]
+enum Mode {
+    Proc,
+    Thread,
 }
 
 /// Struct to manage application state
@@ -229,22 +516,53 @@ struct AppState {
     thread_scroll_position: usize,
     proc_show_count: usize,
     thread_show_count: usize,
-    proc_sort_mode: SortMode,
-    thread_sort_mode: SortMode,
+    proc_sort_mode: ColumnId,
+    proc_sort_order: SortOrder,
+    thread_sort_mode: ThreadSortMode,
+    thread_sort_order: SortOrder,
+    proc_table_area: Rect, // where the tables were last drawn, for header clicks
+    thread_table_area: Rect,
+    filter: ProcessFilter, // applied to the process list and the tree
+    search: Option<SearchPrompt>, // set while the `/` prompt is open
+    columns: ColumnLayout, // process list columns
+    column_picker: Option<ColumnPicker>, // set while the column picker is open
+    signal_menu: Option<SignalMenu>, // set while the signal menu is open
+    pending_signal: Option<PendingSignal>, // set while a confirmation is asked
+    nice_prompt: Option<NicePrompt>, // set while the exact nice prompt is open
+    sched_dialog: Option<SchedDialog>, // set while the scheduler dialog is open
+    io_dialog: Option<IoDialog>, // set while the I/O priority dialog is open
+    affinity_editor: Option<AffinityEditor>, // set while the affinity editor is open
+    detail: Option<DetailView>, // set while the detail view replaces the panels
+    confirm_signals: bool, // ask before sending signals that end processes
+    ascii_tree: bool, // draw the tree's branch guides with ASCII instead of box-drawing characters
+    status: Option<StatusMessage>,
+    config: Config,
+    config_path: Option<PathBuf>,
     frozen: bool,
-    cached_pids: Option<Vec<Pid>>,
+    cached_pids: Option<Vec<u32>>,
+    group_by: Option<GroupBy>, // one row per user or command instead of per process
+    group_drill: Option<String>, // the group whose members are listed
+    cached_groups: Option<Vec<ProcessGroup>>, // the groups as last listed
+    group_cursor: (usize, usize), // scroll position and selection to return to from the members
+    cgroup_view: bool, // the cgroup hierarchy instead of the process list
+    cgroup_pid: Option<u32>, // the process whose cgroup is marked
+    cgroup_drill: Option<String>, // the cgroup whose member processes are listed
+    cgroup_cursor: (usize, usize), // scroll position and selection to return to from the members
     cached_threads: Option<Vec<ThreadInfo>>,
     proc_selected_index: usize,  // Added for process selection
     thread_selected_index: usize,
     show_help: bool,        // Added for help panel toggle
-    killed_pids: Vec<Pid>, // Track killed processes
+    killed_pids: Vec<u32>, // Track killed processes
     cpu_graph: Vec<(f64, f64)>, // track data points for graph 1
-    root_proc: Rc<RefCell<TreeProc>>, // gets the root process and its children
-    curr_sel: u32, // gets the pid of the selected process
-    thread_process_pid: Pid, // stores the process whose thread data is being displayed 
-    thread_samples: HashMap<i32,ThreadSample>,
+    tree_roots: Vec<Rc<RefCell<TreeProc>>>, // init, kthreadd and orphaned processes
+    curr_sel: TreeKey, // the process under the tree cursor
+    tree_cursor_row: usize, // index of the cursor in tree_rows, kept through its process exiting
+    tree_collapsed: HashSet<TreeKey>, // processes whose subtrees are folded away
+    tree_tagged: HashSet<TreeKey>, // processes tagged with `s` for batch actions
+    tree_rows: Vec<TreeRow>, // the tree as last drawn, for navigation
+    tree_page: usize, // rows that fit in the tree table
+    thread_process_pid: u32, // stores the process whose thread data is being displayed 
     latest_thread_count: usize,
-    sel: bool, // used to identify which process is selected for killing
     scroll_offset: usize,
 
 }
@@ -253,8 +571,8 @@ impl AppState {
   fn new(
         proc_show_count: usize,
         thread_show_count: usize,
-        root_proc: Rc<RefCell<TreeProc>>,
-        curr_sel: u32,
+        tree_roots: Vec<Rc<RefCell<TreeProc>>>,
+        curr_sel: TreeKey,
     ) -> Self {
         Self {
             mode: Mode::Proc,
@@ -262,22 +580,53 @@ impl AppState {
             thread_scroll_position: 0,
             proc_show_count,
             thread_show_count,
-            proc_sort_mode: SortMode::Cpu,
-            thread_sort_mode: SortMode::Cpu,
+            proc_sort_mode: ColumnId::Cpu,
+            proc_sort_order: ColumnId::Cpu.default_order(),
+            thread_sort_mode: ThreadSortMode::Cpu,
+            thread_sort_order: ThreadSortMode::Cpu.default_order(),
+            proc_table_area: Rect::default(),
+            thread_table_area: Rect::default(),
+            filter: ProcessFilter::default(),
+            search: None,
+            columns: ColumnLayout::default(),
+            column_picker: None,
+            signal_menu: None,
+            pending_signal: None,
+            nice_prompt: None,
+            sched_dialog: None,
+            io_dialog: None,
+            affinity_editor: None,
+            detail: None,
+            confirm_signals: true,
+            ascii_tree: false,
+            status: None,
+            config: Config::default(),
+            config_path: None,
             frozen: false,
             cached_pids: None,
+            group_by: None,
+            group_drill: None,
+            cached_groups: None,
+            group_cursor: (0, 0),
+            cgroup_view: false,
+            cgroup_pid: None,
+            cgroup_drill: None,
+            cgroup_cursor: (0, 0),
             cached_threads: None,
             proc_selected_index: 0,
             thread_selected_index: 0,
             show_help: false,
             killed_pids: Vec::new(),
             cpu_graph: Vec::new(),
-            root_proc,
+            tree_roots,
             curr_sel,
-            thread_process_pid: Pid::from(1),
-            thread_samples: HashMap::new(),
+            tree_cursor_row: 0,
+            tree_collapsed: HashSet::new(),
+            tree_tagged: HashSet::new(),
+            tree_rows: Vec::new(),
+            tree_page: 1,
+            thread_process_pid: 1,
             latest_thread_count: 1,
-            sel: false,
             scroll_offset: 0,
         }
     }
@@ -323,21 +672,76 @@ impl AppState {
         self.frozen = !self.frozen;
     }
 
-    fn change_sort_mode(&mut self, sortmode: SortMode) {
-        
-        match self.mode
-        {
+    // sorts the process list by a column; choosing the current column again inverts the order
+    fn change_sort_mode(&mut self, column: ColumnId) {
+        if self.proc_sort_mode == column {
+            self.proc_sort_order = self.proc_sort_order.reversed();
+        } else {
+            self.proc_sort_mode = column;
+            self.proc_sort_order = column.default_order();
+        }
+        // Reset cached processes when changing sort mode
+        self.cached_pids = None;
+    }
+
+    fn change_thread_sort_mode(&mut self, sortmode: ThreadSortMode) {
+        if self.thread_sort_mode == sortmode {
+            self.thread_sort_order = self.thread_sort_order.reversed();
+        } else {
+            self.thread_sort_mode = sortmode;
+            self.thread_sort_order = sortmode.default_order();
+        }
+        self.cached_threads = None;
+    }
+
+    fn invert_sort(&mut self) {
+        match self.mode {
             Mode::Proc => {
-                self.proc_sort_mode = sortmode;
-            // Reset cached processes when changing sort mode
+                self.proc_sort_order = self.proc_sort_order.reversed();
                 self.cached_pids = None;
             }
             Mode::Thread => {
-                self.thread_sort_mode = sortmode;
+                self.thread_sort_order = self.thread_sort_order.reversed();
                 self.cached_threads = None;
             }
         }
     }
+
+    // moves the sort key to the next visible column to the left or right
+    fn cycle_sort(&mut self, step: isize) {
+        match self.mode {
+            Mode::Proc => {
+                let visible: Vec<ColumnId> = self.columns.visible().map(|c| c.id).collect();
+                self.change_sort_mode(cycle(&visible, self.proc_sort_mode, step));
+            }
+            Mode::Thread => {
+                self.change_thread_sort_mode(cycle(&ThreadSortMode::ALL, self.thread_sort_mode, step));
+            }
+        }
+    }
+
+    // clicking a table header sorts by the column under the pointer
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        // the group and cgroup tables have their own columns
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || self.showing_groups() || self.showing_cgroups() {
+            return;
+        }
+
+        let proc_widths: Vec<Constraint> = self.columns
+            .visible()
+            .map(|column| column.id.constraint(column.width))
+            .collect();
+        if let Some(idx) = header_column_at(self.proc_table_area, &proc_widths, mouse.column, mouse.row) {
+            let column = self.columns.visible().nth(idx).map(|c| c.id);
+            if let Some(column) = column {
+                self.mode = Mode::Proc;
+                self.change_sort_mode(column);
+            }
+        } else if let Some(idx) = header_column_at(self.thread_table_area, &THREAD_COLUMN_WIDTHS, mouse.column, mouse.row) {
+            self.mode = Mode::Thread;
+            self.change_thread_sort_mode(ThreadSortMode::ALL[idx]);
+        }
+    }
         
     
     // Added methods for selection navigation
@@ -388,146 +792,1008 @@ impl AppState {
     fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
-}
 
-fn percent_color(percent: f64) -> Color{
-    if percent <= 25.0
-    {
-        LIGHT_GREEN
-    }
-    else if percent <= 50.0 
-    {
-        DARK_GREEN
-    }
-    else if percent <= 70.0 
-    {
-        YELLOW
-    }
-    else if percent <= 85.0
-    {
-        ORANGE
-    }
-    else if percent <= 100.0
-    {
-        LIGHT_RED
-    }
-    else {
-        RED
+    // replaces the filter and jumps back to the top of the now different list
+    fn set_filter(&mut self, filter: ProcessFilter) {
+        self.filter = filter;
+        self.cached_pids = None;
+        self.proc_scroll_position = 0;
+        self.proc_selected_index = 0;
     }
-}
 
-fn thread_color(count: usize) -> Color{
-    if count <= 100
-    {
-        LIGHT_GREEN
-    }
-    else if count <= 200
-    {
-        YELLOW
-    }
-    else if count <= 300
-    {
-        ORANGE
-    }
-    else {
-        RED
+    fn open_search(&mut self) {
+        self.search = Some(SearchPrompt {
+            input: self.filter.query().to_string(),
+            previous: self.filter.clone(),
+            error: None,
+        });
     }
-}
 
-fn disk_color(bytes_per_sec: u64) -> Color {
-    if bytes_per_sec <= 100_000_000 {
-        LIGHT_GREEN
-    } else if bytes_per_sec <= 300_000_000 {
-        YELLOW
-    } else if bytes_per_sec <= 500_000_000 {
-        ORANGE
-    } else {
-        RED
+    // edits the search prompt, applying the filter as it is typed
+    fn handle_search_key(&mut self, code: KeyCode) {
+        let Some(mut search) = self.search.take() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Enter => return,
+            KeyCode::Esc => {
+                self.set_filter(search.previous);
+                return;
+            }
+            KeyCode::Backspace => {
+                search.input.pop();
+            }
+            KeyCode::Char(c) => search.input.push(c),
+            _ => {}
+        }
+
+        match ProcessFilter::parse(&search.input) {
+            Ok(filter) => {
+                search.error = None;
+                self.set_filter(filter);
+            }
+            Err(e) => search.error = Some(e),
+        }
+        self.search = Some(search);
     }
-}
-// Function to send signals to the selected process
-fn send_signal_to_selected_process(
-    sys: &System,
-    state: &mut AppState, // Mutable to update killed_pids
-    signal: Signal
-) -> Result<(), nix::Error> {
-    if let Some(pids) = &state.cached_pids {
-        if state.proc_scroll_position + state.proc_selected_index < pids.len() {
-            let index = state.proc_scroll_position + state.proc_selected_index;
-            let pid = pids[index];
 
-            // Verify process still exists
-            if sys.process(pid).is_none() {
-                return Err(nix::Error::ESRCH);
+    // edits the column layout while the picker is open
+    fn handle_column_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = self.column_picker.as_mut() else {
+            return;
+        };
+        let last = self.columns.columns().len().saturating_sub(1);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Esc => self.column_picker = None,
+            KeyCode::Enter => {
+                self.config.columns = self.columns.columns().to_vec();
+                let saved = match &self.config_path {
+                    Some(path) => self.config.save(path),
+                    None => Err("no config path (set $HOME or use --config)".to_string()),
+                };
+                match saved {
+                    Ok(()) => self.column_picker = None,
+                    Err(e) => picker.error = Some(format!("Save failed: {}", e)),
+                }
             }
+            KeyCode::Up if shift => picker.selected = self.columns.move_up(picker.selected),
+            KeyCode::Down if shift => picker.selected = self.columns.move_down(picker.selected),
+            KeyCode::Char('K') => picker.selected = self.columns.move_up(picker.selected),
+            KeyCode::Char('J') => picker.selected = self.columns.move_down(picker.selected),
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Down => picker.selected = (picker.selected + 1).min(last),
+            KeyCode::Left => self.columns.resize(picker.selected, -1),
+            KeyCode::Right => self.columns.resize(picker.selected, 1),
+            KeyCode::Char(' ') => self.columns.toggle(picker.selected),
+            _ => {}
+        }
+    }
 
-            let nix_pid = NixPid::from_raw(pid.as_u32() as i32);
-            kill(nix_pid, signal)?;
+    // opens the signal menu for whatever the next action would apply to, with SIGTERM preselected
+    fn open_signal_menu(&mut self, snapshot: &Snapshot, tree: bool) {
+        let Some(target) = self.action_target(tree) else {
+            self.report_error("Nothing selected".to_string());
+            return;
+        };
 
-            // Add process to killed list if SIGKILL or SIGTERM is sent
-            if signal == Signal::SIGKILL || signal == Signal::SIGTERM {
-                state.killed_pids.push(pid);
+        let mut targets = vec![target.clone()];
+        // a single process can also be reached through its group and session;
+        // kernel threads have neither (0 would mean our own group to killpg)
+        if let Target::Processes(pids) = &target {
+            if let [pid] = pids.as_slice() {
+                if let Some(proc) = snapshot.process(*pid) {
+                    if proc.pgrp > 0 {
+                        let pids = matching_processes(snapshot, |p| p.pgrp == proc.pgrp);
+                        targets.push(Target::Group { pgid: proc.pgrp as u32, pids });
+                    }
+                    if proc.session > 0 {
+                        let pids = matching_processes(snapshot, |p| p.session == proc.session);
+                        targets.push(Target::Session { sid: proc.session as u32, pids });
+                    }
+                }
             }
+        }
 
-            Ok(())
-        } else {
-            Err(nix::Error::ESRCH)
+        let signals = signals::all();
+        let selected = signals.iter().position(|s| s.number == libc::SIGTERM).unwrap_or(0);
+        self.signal_menu = Some(SignalMenu { signals, selected, targets, scope: 0 });
+    }
+
+    fn handle_signal_menu_key(&mut self, key: KeyCode, snapshot: &Snapshot) {
+        let Some(menu) = self.signal_menu.as_mut() else {
+            return;
+        };
+        let last = menu.signals.len().saturating_sub(1);
+
+        match key {
+            KeyCode::Esc => self.signal_menu = None,
+            KeyCode::Up => menu.selected = menu.selected.saturating_sub(1),
+            KeyCode::Down => menu.selected = (menu.selected + 1).min(last),
+            KeyCode::PageUp => menu.selected = menu.selected.saturating_sub(10),
+            KeyCode::PageDown => menu.selected = (menu.selected + 10).min(last),
+            KeyCode::Home => menu.selected = 0,
+            KeyCode::End => menu.selected = last,
+            KeyCode::Tab => menu.scope = (menu.scope + 1) % menu.targets.len(),
+            KeyCode::BackTab => menu.scope = (menu.scope + menu.targets.len() - 1) % menu.targets.len(),
+            KeyCode::Enter => {
+                if let Some(mut menu) = self.signal_menu.take() {
+                    let signal = menu.signals[menu.selected].number;
+                    let target = menu.targets.swap_remove(menu.scope);
+                    self.request_signal(snapshot, Some(target), signal);
+                }
+            }
+            _ => {}
         }
-    } else {
-        Err(nix::Error::ESRCH)
     }
-}
 
-fn send_thread_signal( state: &mut AppState, signal: c_int) -> Result<(), nix::Error>  {
+    // the process(es) or thread the next action applies to: tagged tree processes or
+    // the one under the tree cursor, else the selected process or thread
+    fn action_target(&self, tree: bool) -> Option<Target> {
+        if tree {
+            let pids: Vec<u32> = self.tree_targets().iter().map(|(pid, _)| *pid).collect();
+            return (!pids.is_empty()).then_some(Target::Processes(pids));
+        }
+        // a whole group or cgroup is too broad a target; drill into it to pick processes
+        if (self.showing_groups() || self.showing_cgroups()) && self.mode == Mode::Proc {
+            return None;
+        }
+        match self.mode {
+            Mode::Proc => {
+                let index = self.proc_scroll_position + self.proc_selected_index;
+                let pid = self.cached_pids.as_ref()?.get(index)?;
+                Some(Target::Processes(vec![*pid]))
+            }
+            Mode::Thread => {
+                let index = self.thread_scroll_position + self.thread_selected_index;
+                let thread = self.cached_threads.as_ref()?.get(index)?;
+                Some(Target::Thread {
+                    pid: self.thread_process_pid,
+                    tid: thread.tid,
+                    name: thread.name.clone(),
+                })
+            }
+        }
+    }
 
-    if let Some(threads) = &state.cached_threads {
-        if state.thread_scroll_position + state.thread_selected_index < state.latest_thread_count {
-            let index = state.thread_scroll_position + state.thread_selected_index;
-            let thread = &threads[index];
+    // sends `signal`, first asking in the status bar when it would end the target
+    fn request_signal(&mut self, snapshot: &Snapshot, target: Option<Target>, signal: c_int) {
+        let Some(target) = target else {
+            self.report_error("Nothing selected".to_string());
+            return;
+        };
+        if self.confirm_signals && signals::is_destructive(signal) {
+            let prompt = format!("Send {} to {}? [y/N]", signals::name(signal), target_label(snapshot, &target));
+            self.pending_signal = Some(PendingSignal { target, signal, prompt });
+        } else {
+            self.send_signal(snapshot, &target, signal);
+        }
+    }
 
-            let tgid = state.thread_process_pid;
-            let result = unsafe { syscall(SYS_tgkill, tgid, thread.tid, signal) };
+    fn handle_confirm_key(&mut self, key: KeyCode, snapshot: &Snapshot) {
+        if let Some(pending) = self.pending_signal.take() {
+            match key {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.send_signal(snapshot, &pending.target, pending.signal),
+                _ => self.report("Cancelled".to_string()),
+            }
+        }
+    }
 
-            if result == 0 {
-                Ok(())
-            } else {
-                Err(nix::Error::ESRCH)
+    fn send_signal(&mut self, snapshot: &Snapshot, target: &Target, signal: c_int) {
+        let name = signals::name(signal);
+        let label = target_label(snapshot, target);
+        match target {
+            Target::Processes(pids) | Target::Session { pids, .. } => {
+                let failures = self.signal_pids(pids, signal);
+                match failures.first() {
+                    None => self.report(format!("Sent {} to {}", name, label)),
+                    Some(error) if pids.len() == 1 => self.report_error(error.clone()),
+                    Some(error) => self.report_error(format!(
+                        "Sent {} to {} of {}; {}",
+                        name, pids.len() - failures.len(), label, error)),
+                }
             }
-        } else {
-            Err(nix::Error::ESRCH)
+            Target::Group { pgid, pids } => {
+                match Errno::result(unsafe { libc::killpg(*pgid as libc::pid_t, signal) }) {
+                    Ok(_) => {
+                        if signal == libc::SIGKILL || signal == libc::SIGTERM {
+                            self.killed_pids.extend(pids);
+                        }
+                        self.report(format!("Sent {} to {}", name, label));
+                    }
+                    Err(e) => self.report_error(signal_error(e, *pgid)),
+                }
             }
-    } else {
-        Err(nix::Error::ESRCH)
+            Target::Thread { pid, tid, .. } => {
+                let result = Errno::result(unsafe { syscall(SYS_tgkill, *pid as c_int, *tid as c_int, signal) });
+                match result {
+                    Ok(_) => self.report(format!("Sent {} to {}", name, label)),
+                    Err(e) => self.report_error(signal_error(e, *tid)),
+                }
+            }
+        }
     }
 
-    
-   
-}
+    // kill(2)s each PID, returning a message for every failure
+    fn signal_pids(&mut self, pids: &[u32], signal: c_int) -> Vec<String> {
+        let mut failures = Vec::new();
+        for pid in pids {
+            match kill_pid(*pid, signal) {
+                Ok(()) if signal == libc::SIGKILL || signal == libc::SIGTERM => self.killed_pids.push(*pid),
+                Ok(()) => {}
+                Err(e) => failures.push(signal_error(e, *pid)),
+            }
+        }
+        failures
+    }
 
-// to get the selected process to display its threads
-fn selected_pid(state: &AppState) -> Pid{
-    if let Some(pids) = &state.cached_pids {
-        let index = state.proc_scroll_position + state.proc_selected_index;
-        if index < pids.len() {
-            return pids[index]
+    // sets the nice value of every process in the target to `new_nice(current)`
+    fn renice(&mut self, snapshot: &Snapshot, target: Option<Target>, new_nice: impl Fn(i32) -> i32) {
+        let Some(target) = target else {
+            return self.report_error("Nothing selected".to_string());
+        };
+        let pids = target.pids(); // setpriority takes a TID too
+
+        let mut last_nice = 0;
+        for pid in &pids {
+            let niceval = match get_nice(*pid) {
+                Ok(nice) => nice,
+                Err(e) => return self.report_error(renice_error(e, *pid)),
+            };
+            last_nice = new_nice(niceval).clamp(-20, 19);
+            if last_nice == niceval {
+                continue;
+            }
+            if let Err(e) = renice_process(*pid, last_nice) {
+                return self.report_error(renice_error(e, *pid));
+            }
+        }
+        let label = target_label(snapshot, &target);
+        if pids.len() == 1 {
+            self.report(format!("Reniced {} to {}", label, last_nice));
+        } else {
+            self.report(format!("Reniced {}", label));
         }
     }
 
-    // Returns the currently selected PID from the cached list.
-    // If the selection is invalid, returns PID 1 (init/systemd) as a failsafe.
-    sysinfo::Pid::from(1)
-}
+    // opens the prompt for an exact nice value
+    fn open_nice_prompt(&mut self, snapshot: &Snapshot, tree: bool) {
+        let Some(target) = self.action_target(tree) else {
+            return self.report_error("Nothing selected".to_string());
+        };
+        let current = match target.pids().first().map(|pid| (*pid, get_nice(*pid))) {
+            Some((pid, Err(e))) => return self.report_error(renice_error(e, pid)),
+            Some((_, Ok(nice))) => Some(nice),
+            None => None,
+        };
+        let prompt = format!("Nice value for {} [-20..19]: ", target_label(snapshot, &target));
+        self.nice_prompt = Some(NicePrompt {
+            input: current.map(|nice| nice.to_string()).unwrap_or_default(),
+            prompt,
+            target,
+            error: None,
+        });
+    }
 
+    fn handle_nice_prompt_key(&mut self, code: KeyCode, snapshot: &Snapshot) {
+        let Some(mut prompt) = self.nice_prompt.take() else {
+            return;
+        };
 
-fn renice_process(pid: Pid, new_nice: i32) -> Result<(), String> {
-    let ret = unsafe { setpriority(PRIO_PROCESS, pid.as_u32(), new_nice) };
-    if ret == 0 {
-        Ok(())
-    } else {
-        Err(format!("Failed to renice PID {} to {}: {}", pid, new_nice, std::io::Error::last_os_error()))
+        match code {
+            KeyCode::Esc => return,
+            KeyCode::Enter => match prompt.input.trim().parse::<i32>() {
+                Ok(nice) if (-20..=19).contains(&nice) => {
+                    return self.renice(snapshot, Some(prompt.target), |_| nice);
+                }
+                _ => prompt.error = Some("enter a number from -20 to 19".to_string()),
+            },
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '-' => prompt.input.push(c),
+            _ => {}
+        }
+        self.nice_prompt = Some(prompt);
     }
-}
- 
+
+    // opens the scheduler dialog showing the current policy of the first target
+    fn open_sched_dialog(&mut self, tree: bool) {
+        let Some(target) = self.action_target(tree) else {
+            return self.report_error("Nothing selected".to_string());
+        };
+        let current = target.pids().first().map(|pid| sched::get_scheduler(*pid));
+        let (policy, priority) = match current {
+            Some(Ok(current)) => current,
+            Some(Err(e)) => return self.report_error(format!("{}: could not read the scheduling policy", e)),
+            None => return,
+        };
+        self.sched_dialog = Some(SchedDialog {
+            target,
+            current: (policy, priority),
+            policy,
+            priority: priority.max(1),
+        });
+    }
+
+    fn handle_sched_dialog_key(&mut self, key: KeyEvent, snapshot: &Snapshot) {
+        let Some(dialog) = self.sched_dialog.as_mut() else {
+            return;
+        };
+        let selected = Policy::ALL.iter().position(|p| *p == dialog.policy).unwrap_or(0);
+        let step = if key.modifiers.contains(KeyModifiers::SHIFT) { 10 } else { 1 };
+
+        match key.code {
+            KeyCode::Esc => self.sched_dialog = None,
+            KeyCode::Up => dialog.policy = Policy::ALL[selected.saturating_sub(1)],
+            KeyCode::Down => dialog.policy = Policy::ALL[(selected + 1).min(Policy::ALL.len() - 1)],
+            KeyCode::Left => dialog.priority = (dialog.priority - step).max(1),
+            KeyCode::Right => dialog.priority = (dialog.priority + step).min(99),
+            KeyCode::Enter => {
+                if let Some(dialog) = self.sched_dialog.take() {
+                    self.set_scheduler(snapshot, &dialog.target, dialog.policy, dialog.priority);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_scheduler(&mut self, snapshot: &Snapshot, target: &Target, policy: Policy, priority: i32) {
+        for pid in target.pids() {
+            if let Err(e) = sched::set_scheduler(pid, policy, priority) {
+                return self.report_error(sched_error(e, policy, pid));
+            }
+        }
+        let label = target_label(snapshot, target);
+        if policy.is_realtime() {
+            self.report(format!("Set {} to {} priority {}", label, policy.name(), priority));
+        } else {
+            self.report(format!("Set {} to {}", label, policy.name()));
+        }
+    }
+
+    // opens the I/O priority dialog showing the class of the first target
+    fn open_io_dialog(&mut self, tree: bool) {
+        let Some(target) = self.action_target(tree) else {
+            return self.report_error("Nothing selected".to_string());
+        };
+        let Some(pid) = target.pids().first().copied() else {
+            return;
+        };
+        let current = match sched::get_io_priority(pid) {
+            Ok(current) => current,
+            Err(e) => return self.report_error(format!("{}: could not read the I/O priority", e)),
+        };
+        let nice = match get_nice(pid) {
+            Ok(nice) => nice as i64,
+            Err(e) => return self.report_error(renice_error(e, pid)),
+        };
+        let effective = current.effective(nice);
+        self.io_dialog = Some(IoDialog {
+            target,
+            current,
+            nice,
+            class: effective.class,
+            level: effective.level,
+        });
+    }
+
+    fn handle_io_dialog_key(&mut self, code: KeyCode, snapshot: &Snapshot) {
+        let Some(dialog) = self.io_dialog.as_mut() else {
+            return;
+        };
+        let selected = IoClass::SETTABLE.iter().position(|c| *c == dialog.class).unwrap_or(0);
+
+        match code {
+            KeyCode::Esc => self.io_dialog = None,
+            KeyCode::Up => dialog.class = IoClass::SETTABLE[selected.saturating_sub(1)],
+            KeyCode::Down => dialog.class = IoClass::SETTABLE[(selected + 1).min(IoClass::SETTABLE.len() - 1)],
+            KeyCode::Left => dialog.level = (dialog.level - 1).max(0),
+            KeyCode::Right => dialog.level = (dialog.level + 1).min(7),
+            KeyCode::Enter => {
+                if let Some(dialog) = self.io_dialog.take() {
+                    self.set_io_priority(snapshot, &dialog.target, dialog.class, dialog.level);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_io_priority(&mut self, snapshot: &Snapshot, target: &Target, class: IoClass, level: i32) {
+        for pid in target.pids() {
+            if let Err(e) = sched::set_io_priority(pid, class, level) {
+                return self.report_error(io_priority_error(e, class, pid));
+            }
+        }
+        let label = target_label(snapshot, target);
+        if class.has_level() {
+            self.report(format!("Set I/O priority of {} to {} {}", label, class.name(), level));
+        } else {
+            self.report(format!("Set I/O priority of {} to {}", label, class.name()));
+        }
+    }
+
+    // opens the affinity editor with the CPUs of the first target checked
+    fn open_affinity_editor(&mut self, snapshot: &Snapshot, tree: bool) {
+        let Some(target) = self.action_target(tree) else {
+            return self.report_error("Nothing selected".to_string());
+        };
+        let Some(pid) = target.pids().first().copied() else {
+            return;
+        };
+        let allowed = match sched::get_affinity(pid) {
+            Ok(allowed) => allowed,
+            Err(e) => return self.report_error(format!("{}: could not read the CPU affinity", e)),
+        };
+        let cpus = (0..snapshot.cpus.len()).map(|cpu| allowed.contains(&cpu)).collect();
+        self.affinity_editor = Some(AffinityEditor { target, cpus, selected: 0, error: None });
+    }
+
+    fn handle_affinity_editor_key(&mut self, code: KeyCode, snapshot: &Snapshot) {
+        let Some(editor) = self.affinity_editor.as_mut() else {
+            return;
+        };
+        let last = editor.cpus.len().saturating_sub(1);
+        editor.error = None;
+
+        match code {
+            KeyCode::Esc => self.affinity_editor = None,
+            KeyCode::Up => editor.selected = editor.selected.saturating_sub(1),
+            KeyCode::Down => editor.selected = (editor.selected + 1).min(last),
+            KeyCode::PageUp => editor.selected = editor.selected.saturating_sub(10),
+            KeyCode::PageDown => editor.selected = (editor.selected + 10).min(last),
+            KeyCode::Home => editor.selected = 0,
+            KeyCode::End => editor.selected = last,
+            KeyCode::Char(' ') => {
+                if let Some(checked) = editor.cpus.get_mut(editor.selected) {
+                    *checked = !*checked;
+                }
+            }
+            // checks every CPU, or clears them all when they already are
+            KeyCode::Char('a') => {
+                let all = editor.cpus.iter().all(|checked| *checked);
+                editor.cpus.iter_mut().for_each(|checked| *checked = !all);
+            }
+            KeyCode::Enter => {
+                let cpus: Vec<usize> = editor.cpus
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, checked)| **checked)
+                    .map(|(cpu, _)| cpu)
+                    .collect();
+                if cpus.is_empty() {
+                    editor.error = Some("Select at least one CPU".to_string());
+                    return;
+                }
+                if let Some(editor) = self.affinity_editor.take() {
+                    self.set_affinity(snapshot, &editor.target, &cpus);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_affinity(&mut self, snapshot: &Snapshot, target: &Target, cpus: &[usize]) {
+        // a thread is pinned alone, a process with all of its threads
+        let result = match target {
+            Target::Thread { tid, .. } => sched::set_affinity(*tid, cpus).map_err(|e| (e, *tid)),
+            _ => target
+                .pids()
+                .into_iter()
+                .try_for_each(|pid| sched::set_process_affinity(pid, cpus).map_err(|e| (e, pid))),
+        };
+        if let Err((e, pid)) = result {
+            return self.report_error(affinity_error(e, pid));
+        }
+        self.report(format!(
+            "Pinned {} to CPU {}",
+            target_label(snapshot, target),
+            sched::format_cpu_list(cpus)
+        ));
+    }
+
+    fn showing_groups(&self) -> bool {
+        self.group_by.is_some() && self.group_drill.is_none()
+    }
+
+    fn selected_group(&self) -> Option<&ProcessGroup> {
+        let index = self.proc_scroll_position + self.proc_selected_index;
+        self.cached_groups.as_ref()?.get(index)
+    }
+
+    // whether a process is listed: it matches the filter and, while drilled into a group
+    // or cgroup, is a member
+    fn is_listed(&self, proc: &ProcessInfo) -> bool {
+        let member = match (self.group_by, &self.group_drill) {
+            (Some(by), Some(key)) => !proc.is_thread && by.key(proc) == *key,
+            _ => true,
+        };
+        // in cgroup v2 only leaves hold processes, so a group lists its descendants' members too;
+        // the path is read directly when the snapshot was taken before paths were collected
+        let in_cgroup = match &self.cgroup_drill {
+            Some(path) => proc
+                .cgroup
+                .clone()
+                .or_else(|| cgroup::process_cgroup(proc.pid))
+                .is_some_and(|c| cgroup::is_within(&c, path)),
+            None => true,
+        };
+        member && in_cgroup && self.filter.matches(proc)
+    }
+
+    // switches between the process list and the per-user and per-command groups
+    fn cycle_grouping(&mut self) {
+        self.group_by = match self.group_by {
+            None => Some(GroupBy::ALL[0]),
+            Some(by) => GroupBy::ALL.iter().skip_while(|b| **b != by).nth(1).copied(),
+        };
+        self.group_drill = None;
+        self.cached_groups = None;
+        self.cgroup_view = false;
+        self.cgroup_drill = None;
+        self.cached_pids = None;
+        self.mode = Mode::Proc;
+        self.proc_scroll_position = 0;
+        self.proc_selected_index = 0;
+    }
+
+    // lists the members of the selected group
+    fn drill_into_group(&mut self) {
+        let Some(key) = self.selected_group().map(|group| group.key.clone()) else {
+            return;
+        };
+        self.group_cursor = (self.proc_scroll_position, self.proc_selected_index);
+        self.group_drill = Some(key);
+        self.cached_pids = None;
+        self.proc_scroll_position = 0;
+        self.proc_selected_index = 0;
+    }
+
+    // goes back from the members to the groups, where the cursor was
+    fn leave_group(&mut self) {
+        self.group_drill = None;
+        (self.proc_scroll_position, self.proc_selected_index) = self.group_cursor;
+    }
+
+    fn showing_cgroups(&self) -> bool {
+        self.cgroup_view && self.cgroup_drill.is_none()
+    }
+
+    // switches between the process list and the cgroup hierarchy, marking the cgroup
+    // of the selected process
+    fn toggle_cgroups(&mut self) {
+        if !self.cgroup_view {
+            self.cgroup_pid = match self.mode {
+                Mode::Proc if !self.showing_groups() => {
+                    let index = self.proc_scroll_position + self.proc_selected_index;
+                    self.cached_pids.as_ref().and_then(|pids| pids.get(index)).copied()
+                }
+                Mode::Proc => None,
+                Mode::Thread => Some(self.thread_process_pid),
+            };
+        }
+        self.cgroup_view = !self.cgroup_view;
+        self.cgroup_drill = None;
+        self.group_by = None;
+        self.group_drill = None;
+        self.cached_groups = None;
+        self.cached_pids = None;
+        self.mode = Mode::Proc;
+        self.proc_scroll_position = 0;
+        self.proc_selected_index = 0;
+    }
+
+    // moves the cursor to the cgroup of the marked process
+    fn select_marked_cgroup(&mut self, snapshot: &Snapshot) {
+        let Some(path) = marked_cgroup(snapshot, self) else {
+            return;
+        };
+        if let Some(index) = snapshot.cgroups.iter().position(|c| c.path == path) {
+            self.proc_scroll_position = index.saturating_sub(self.proc_show_count.saturating_sub(1) / 2);
+            self.proc_selected_index = index - self.proc_scroll_position;
+        }
+    }
+
+    // lists the processes in the selected cgroup
+    fn drill_into_cgroup(&mut self, snapshot: &Snapshot) {
+        let index = self.proc_scroll_position + self.proc_selected_index;
+        let Some(path) = snapshot.cgroups.get(index).map(|c| c.path.clone()) else {
+            return;
+        };
+        self.cgroup_cursor = (self.proc_scroll_position, self.proc_selected_index);
+        self.cgroup_drill = Some(path);
+        self.cached_pids = None;
+        self.proc_scroll_position = 0;
+        self.proc_selected_index = 0;
+    }
+
+    // goes back from the members to the hierarchy, where the cursor was
+    fn leave_cgroup(&mut self) {
+        self.cgroup_drill = None;
+        (self.proc_scroll_position, self.proc_selected_index) = self.cgroup_cursor;
+    }
+
+    // opens the detail view of the process under the cursor
+    fn open_detail(&mut self, snapshot: &Snapshot, tree: bool) {
+        let pid = if tree {
+            Some(self.curr_sel.0)
+        } else {
+            match self.mode {
+                Mode::Proc => {
+                    let index = self.proc_scroll_position + self.proc_selected_index;
+                    self.cached_pids.as_ref().and_then(|pids| pids.get(index)).copied()
+                }
+                Mode::Thread => Some(self.thread_process_pid),
+            }
+        };
+        let Some(pid) = pid else {
+            return self.report_error("Nothing selected".to_string());
+        };
+        let name = snapshot.process(pid).map(|p| p.name.clone()).unwrap_or_default();
+        self.detail = Some(DetailView::new(pid, name));
+    }
+
+    fn handle_detail_key(&mut self, code: KeyCode) {
+        let Some(view) = self.detail.as_mut() else {
+            return;
+        };
+
+        // the filter box takes every key until Enter keeps or Esc clears the filter
+        if view.editing_filter {
+            match code {
+                KeyCode::Enter => view.editing_filter = false,
+                KeyCode::Esc => {
+                    view.filter.clear();
+                    view.editing_filter = false;
+                }
+                KeyCode::Backspace => {
+                    view.filter.pop();
+                }
+                KeyCode::Char(c) => view.filter.push(c),
+                _ => {}
+            }
+            view.scroll = 0;
+            return;
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace => self.detail = None,
+            KeyCode::Char('/') if view.tab == DetailTab::Fds => view.editing_filter = true,
+            KeyCode::Char('s') if view.tab == DetailTab::Maps => {
+                view.map_sort = cycle(&MapSortKey::ALL, view.map_sort, 1);
+                view.map_order = view.map_sort.default_order();
+            }
+            KeyCode::Char('i') if view.tab == DetailTab::Maps => view.map_order = view.map_order.reversed(),
+            KeyCode::Char('g') if view.tab == DetailTab::Maps => {
+                view.map_grouped = !view.map_grouped;
+                view.scroll = 0;
+            }
+            KeyCode::Right | KeyCode::Tab => view.set_tab(cycle(&DetailTab::ALL, view.tab, 1)),
+            KeyCode::Left | KeyCode::BackTab => view.set_tab(cycle(&DetailTab::ALL, view.tab, -1)),
+            KeyCode::Char(c @ '1'..='9') => view.set_tab(DetailTab::ALL[c as usize - '1' as usize]),
+            KeyCode::Char('0') => view.set_tab(DetailTab::ALL[9]),
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = view.scroll.saturating_add(1),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(20),
+            KeyCode::PageDown => view.scroll = view.scroll.saturating_add(20),
+            KeyCode::Home => view.scroll = 0,
+            _ => {}
+        }
+    }
+
+    fn report(&mut self, text: String) {
+        self.status = Some(StatusMessage { text, error: false, since: Instant::now() });
+    }
+
+    fn report_error(&mut self, text: String) {
+        self.status = Some(StatusMessage { text, error: true, since: Instant::now() });
+    }
+
+    // index of the tree cursor in the rows last drawn
+    fn tree_index(&self) -> usize {
+        self.tree_cursor_row.min(self.tree_rows.len().saturating_sub(1))
+    }
+
+    // puts the tree cursor on a row
+    fn tree_select(&mut self, idx: usize) {
+        if let Some(row) = self.tree_rows.get(idx) {
+            self.curr_sel = row.node.borrow().get_key();
+            self.tree_cursor_row = idx;
+        }
+    }
+
+    // moves the tree cursor by `step` rows, wrapping around for single steps
+    fn tree_move(&mut self, step: isize) {
+        let len = self.tree_rows.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let idx = self.tree_index() as isize + step;
+        let idx = if step.abs() == 1 { idx.rem_euclid(len) } else { idx.clamp(0, len - 1) };
+        self.tree_select(idx as usize);
+    }
+
+    // Left folds an open subtree, or jumps to the parent when there is nothing to fold
+    fn tree_collapse(&mut self) {
+        let Some(row) = self.tree_rows.get(self.tree_index()) else {
+            return;
+        };
+        if row.has_children && !row.collapsed {
+            self.tree_collapsed.insert(row.node.borrow().get_key());
+        } else if let Some(parent) = row.parent {
+            // the parent is drawn above its children
+            let idx = self.tree_index();
+            if let Some(offset) = self.tree_rows[..idx].iter().rposition(|row| row.node.borrow().get_key() == parent) {
+                self.tree_select(offset);
+            }
+        }
+    }
+
+    // Right unfolds a collapsed subtree, or steps into an open one
+    fn tree_expand(&mut self) {
+        let Some(row) = self.tree_rows.get(self.tree_index()) else {
+            return;
+        };
+        if row.collapsed {
+            self.tree_collapsed.remove(&row.node.borrow().get_key());
+        } else if row.has_children {
+            self.tree_move(1);
+        }
+    }
+
+    // tags or untags the process under the tree cursor
+    fn tree_tag(&mut self, tagged: bool) {
+        if let Some(row) = self.tree_rows.get(self.tree_index()) {
+            let key = row.node.borrow().get_key();
+            // a signal or renice sent to a TID reaches its whole process, so tag that instead
+            if tagged && row.node.borrow().is_thread {
+                return self.report_error(format!("{} is a thread; tag its process instead", key.0));
+            }
+            if tagged {
+                self.tree_tagged.insert(key);
+            } else {
+                self.tree_tagged.remove(&key);
+            }
+        }
+    }
+
+    // tagged processes, or the one under the cursor when nothing is tagged; a thread
+    // under the cursor stands for the process it belongs to
+    fn tree_targets(&self) -> Vec<TreeKey> {
+        if self.tree_tagged.is_empty() {
+            return self.tree_rows
+                .get(self.tree_index())
+                .and_then(|row| if row.node.borrow().is_thread { row.parent } else { Some(row.node.borrow().get_key()) })
+                .into_iter()
+                .collect();
+        }
+        let mut keys: Vec<TreeKey> = self.tree_tagged.iter().copied().collect();
+        keys.sort();
+        keys
+    }
+
+    // the targets and everything below them, children before their parents
+    fn tree_target_subtrees(&self) -> Vec<TreeKey> {
+        let nodes = tree_nodes(&self.tree_roots);
+        let mut seen = HashSet::new();
+        let mut keys = Vec::new();
+        for key in self.tree_targets() {
+            if let Some(node) = nodes.get(&key) {
+                tree_postorder(node, &mut seen, &mut keys);
+            }
+        }
+        keys
+    }
+
+    // tags or untags the process under the cursor together with all of its descendants
+    fn tree_tag_subtree(&mut self, tagged: bool) {
+        let Some(row) = self.tree_rows.get(self.tree_index()) else {
+            return;
+        };
+        let mut keys = Vec::new();
+        tree_postorder(&row.node, &mut HashSet::new(), &mut keys);
+        for key in keys {
+            if tagged {
+                self.tree_tagged.insert(key);
+            } else {
+                self.tree_tagged.remove(&key);
+            }
+        }
+    }
+
+    // rebuilds the tree from a fresh snapshot; folds and tags of processes that
+    // exited are dropped so a reused PID starts out unfolded and untagged
+    fn rebuild_tree(&mut self, snapshot: &Snapshot) {
+        self.tree_roots = tree_create(snapshot);
+        let alive: HashSet<TreeKey> = snapshot.processes
+            .iter()
+            .map(|proc| (proc.pid, proc.start_time))
+            .collect();
+        self.tree_collapsed.retain(|key| alive.contains(key));
+        self.tree_tagged.retain(|key| alive.contains(key));
+    }
+
+    // title fragment describing the active filter or the open prompt
+    fn filter_title(&self) -> String {
+        match &self.search {
+            Some(search) => match &search.error {
+                Some(error) => format!(" [/{}█ ({})]", search.input, error),
+                None => format!(" [/{}█]", search.input),
+            },
+            None if !self.filter.is_empty() => format!(" [Filter: {}]", self.filter.query()),
+            None => String::new(),
+        }
+    }
+}
+
+fn percent_color(percent: f64) -> Color{
+    if percent <= 25.0
+    {
+        LIGHT_GREEN
+    }
+    else if percent <= 50.0 
+    {
+        DARK_GREEN
+    }
+    else if percent <= 70.0 
+    {
+        YELLOW
+    }
+    else if percent <= 85.0
+    {
+        ORANGE
+    }
+    else if percent <= 100.0
+    {
+        LIGHT_RED
+    }
+    else {
+        RED
+    }
+}
+
+fn thread_color(count: usize) -> Color{
+    if count <= 100
+    {
+        LIGHT_GREEN
+    }
+    else if count <= 200
+    {
+        YELLOW
+    }
+    else if count <= 300
+    {
+        ORANGE
+    }
+    else {
+        RED
+    }
+}
+
+fn disk_color(bytes_per_sec: u64) -> Color {
+    if bytes_per_sec <= 100_000_000 {
+        LIGHT_GREEN
+    } else if bytes_per_sec <= 300_000_000 {
+        YELLOW
+    } else if bytes_per_sec <= 500_000_000 {
+        ORANGE
+    } else {
+        RED
+    }
+}
+// kill(2) with a raw signal number, so real-time signals can be sent too
+fn kill_pid(pid: u32, signal: c_int) -> Result<(), nix::Error> {
+    Errno::result(unsafe { libc::kill(pid as libc::pid_t, signal) }).map(drop)
+}
+
+// "PID 42 (name)" for titles and messages
+fn process_label(snapshot: &Snapshot, pid: u32) -> String {
+    match snapshot.process(pid) {
+        Some(proc) => format!("PID {} ({})", pid, proc.name),
+        None => format!("PID {}", pid),
+    }
+}
+
+// "PID 42 (name)", "TID 43 (name)" or "3 processes" for prompts and messages
+fn target_label(snapshot: &Snapshot, target: &Target) -> String {
+    match target {
+        Target::Processes(pids) if pids.len() == 1 => process_label(snapshot, pids[0]),
+        Target::Processes(pids) => format!("{} processes", pids.len()),
+        Target::Thread { tid, name, .. } => format!("TID {} ({})", tid, name),
+        Target::Group { pgid, pids } => format!("process group {} ({} processes)", pgid, pids.len()),
+        Target::Session { sid, pids } => format!("session {} ({} processes)", sid, pids.len()),
+    }
+}
+
+// processes (not threads) of the snapshot for which `member` holds, by PID
+fn matching_processes(snapshot: &Snapshot, member: impl Fn(&ProcessInfo) -> bool) -> Vec<u32> {
+    snapshot.processes
+        .iter()
+        .filter(|proc| !proc.is_thread && member(proc))
+        .map(|proc| proc.pid)
+        .collect()
+}
+
+// status bar text for a failed kill(2)/tgkill(2)
+fn signal_error(e: Errno, pid: u32) -> String {
+    match e {
+        Errno::EPERM => format!("EPERM: not permitted to signal {} (owned by another user?)", pid),
+        Errno::ESRCH => format!("ESRCH: {} no longer exists", pid),
+        e => format!("{}: could not signal {}", e, pid),
+    }
+}
+
+// status bar text for a failed sched_setscheduler(2)
+fn sched_error(e: Errno, policy: Policy, pid: u32) -> String {
+    match e {
+        Errno::EPERM if policy.is_realtime() => "EPERM: real-time policies require CAP_SYS_NICE".to_string(),
+        Errno::EPERM => format!("EPERM: not permitted to change the policy of {}", pid),
+        Errno::ESRCH => format!("ESRCH: {} no longer exists", pid),
+        e => format!("{}: could not set {} on {}", e, policy.name(), pid),
+    }
+}
+
+// status bar text for a failed ioprio_set(2)
+fn io_priority_error(e: Errno, class: IoClass, pid: u32) -> String {
+    match e {
+        Errno::EPERM if class == IoClass::Realtime => "EPERM: the realtime class requires CAP_SYS_ADMIN".to_string(),
+        Errno::EPERM => format!("EPERM: not permitted to change the I/O priority of {}", pid),
+        Errno::ESRCH => format!("ESRCH: {} no longer exists", pid),
+        e => format!("{}: could not set the {} class on {}", e, class.name(), pid),
+    }
+}
+
+// status bar text for a failed sched_setaffinity(2)
+fn affinity_error(e: Errno, pid: u32) -> String {
+    match e {
+        Errno::EPERM => format!("EPERM: not permitted to change the affinity of {} (needs CAP_SYS_NICE)", pid),
+        Errno::ESRCH => format!("ESRCH: {} no longer exists", pid),
+        Errno::EINVAL => format!("EINVAL: none of the CPUs are usable by {} (cpuset)", pid),
+        e => format!("{}: could not set the affinity of {}", e, pid),
+    }
+}
+
+// status bar text for a failed setpriority(2)
+fn renice_error(e: Errno, pid: u32) -> String {
+    match e {
+        Errno::EACCES => "EACCES: lowering the nice value requires CAP_SYS_NICE".to_string(),
+        Errno::EPERM => "EPERM: renice requires CAP_SYS_NICE".to_string(),
+        Errno::ESRCH => format!("ESRCH: {} no longer exists", pid),
+        e => format!("{}: could not renice {}", e, pid),
+    }
+}
+
+// to get the selected process to display its threads
+fn selected_pid(state: &AppState) -> u32{
+    if let Some(pids) = &state.cached_pids {
+        let index = state.proc_scroll_position + state.proc_selected_index;
+        if index < pids.len() {
+            return pids[index]
+        }
+    }
+
+    // Returns the currently selected PID from the cached list.
+    // If the selection is invalid, returns PID 1 (init/systemd) as a failsafe.
+    1
+}
+
+
+// getpriority(2) returns -1 both on error and for a nice of -1, so errno tells them apart
+fn get_nice(pid: u32) -> Result<i32, Errno> {
+    Errno::clear();
+    let nice = unsafe { getpriority(PRIO_PROCESS, pid) };
+    match Errno::last() {
+        e if nice == -1 && e != Errno::UnknownErrno => Err(e),
+        _ => Ok(nice),
+    }
+}
+
+fn renice_process(pid: u32, new_nice: i32) -> Result<(), Errno> {
+    Errno::result(unsafe { setpriority(PRIO_PROCESS, pid, new_nice) }).map(drop)
+}
+ 
 
 
 
@@ -563,7 +1829,7 @@ fn help_panel<'a>() -> Table<'a> {
         Row::new(vec![
             Cell::from("P: Suspend"),
             Cell::from("←/→ : Switch Between Processes and Threads"),
-            Cell::from("3: Sort by PID/TID"),
+            Cell::from("3: Sort by PID/TID (Again: Invert)"),
         ]),
         Row::new(vec![
             Cell::from("R: Resume"),
@@ -574,7 +1840,7 @@ fn help_panel<'a>() -> Table<'a> {
             )),
         ]),
         Row::new(vec![
-            Cell::from("+: Renice Selected Process"),
+            Cell::from("+/-: Renice  N: Set Nice  O: Scheduler Policy"),
             Cell::from(Span::styled(
                 "Tree Selection",
                 Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD),
@@ -583,13 +1849,28 @@ fn help_panel<'a>() -> Table<'a> {
         ]),
         Row::new(vec![
             Cell::from("→ : Show Thread Data for Selected Process"),
-            Cell::from("S: Select Process"),
+            Cell::from("S: Tag Process  ←/→: Collapse/Expand"),
             Cell::from("Q: Quit Application"),
         ]),
         Row::new(vec![
-            Cell::from(""),
-            Cell::from("D: Deselect Process"),
-            Cell::from(""),
+            Cell::from("X: Kill Subtree, Children First [TREE]"),
+            Cell::from("D: Untag Process"),
+            Cell::from("/: Search/Filter (Esc: Clear)"),
+        ]),
+        Row::new(vec![
+            Cell::from("K/U/P/R/+/-: Act on Tagged [TREE]"),
+            Cell::from("Shift+S/D: Tag/Untag Subtree"),
+            Cell::from("C: Choose Columns"),
+        ]),
+        Row::new(vec![
+            Cell::from("Shift+K/F9: Signal Process/Group/Session"),
+            Cell::from("Shift+I: I/O Priority  A: CPU Affinity"),
+            Cell::from("</>: Sort Column  I: Invert  Click Header: Sort"),
+        ]),
+        Row::new(vec![
+            Cell::from("Enter: Process Details"),
+            Cell::from("Shift+G: Group by User/Command/Owner"),
+            Cell::from("Shift+C: Cgroups (Enter: Processes)"),
         ]),
         
     ];
@@ -628,8 +1909,8 @@ fn format_uptime(seconds: u64) -> String {
     }
 }
 
-fn system_info(_sys: &sysinfo::System) -> Table {
-    let sys_titles = vec![
+fn system_info(snapshot: &Snapshot) -> Table<'_> {
+    let sys_titles = [
     "System Name:",
     "System Kernel Version:",
     "System OS Version:",
@@ -637,16 +1918,14 @@ fn system_info(_sys: &sysinfo::System) -> Table {
     "System Uptime:",
     "NB CPUs:",
     ];
-    let sys = System::new_all();
-
 
-    let sys_values = vec![
-        System::name().unwrap_or("Unknown".to_string()),
-        System::kernel_version().unwrap_or("Unknown".to_string()),
-        System::os_version().unwrap_or("Unknown".to_string()),
-        System::host_name().unwrap_or("Unknown".to_string()),
-        format_uptime(System::uptime()),
-        format!("{}", sys.cpus().len()),
+    let sys_values = [
+        snapshot.system.name.clone(),
+        snapshot.system.kernel_version.clone(),
+        snapshot.system.os_version.clone(),
+        snapshot.system.host_name.clone(),
+        format_uptime(snapshot.system.uptime),
+        format!("{}", snapshot.cpus.len()),
     ];
 
     let rows: Vec<Row> = sys_titles.iter().zip(sys_values.iter()).map(|(title, value)|{
@@ -668,19 +1947,19 @@ fn system_info(_sys: &sysinfo::System) -> Table {
                    
 }
 
-fn _usage_info(sys: &sysinfo::System) -> Table {
-    let sys_titles = vec![
+fn _usage_info(snapshot: &Snapshot) -> Table<'_> {
+    let sys_titles = [
     "Total Memory:",
     "Used Memory:",
     "Total Swap:",
     "Used Swap:"
     ];
 
-    let sys_values = vec![
-        format!("{} MB", sys.total_memory() / (1024*1024)),
-        format!("{} MB", sys.used_memory() / (1024*1024)),
-        format!("{} MB", sys.total_swap() / (1024*1024)),
-        format!("{} MB", sys.used_swap() / (1024*1024))
+    let sys_values = [
+        format!("{} MB", snapshot.memory.total / (1024*1024)),
+        format!("{} MB", snapshot.memory.used / (1024*1024)),
+        format!("{} MB", snapshot.memory.swap_total / (1024*1024)),
+        format!("{} MB", snapshot.memory.swap_used / (1024*1024))
     ];
 
     let rows: Vec<Row> = sys_titles.iter().zip(sys_values.iter()).map(|(title, value)|{
@@ -702,18 +1981,18 @@ fn _usage_info(sys: &sysinfo::System) -> Table {
                    
 }
 
-fn cpu_info(sys: &sysinfo::System) -> Table{
+fn cpu_info(snapshot: &Snapshot) -> Table<'_>{
 
-    let rows: Vec<Row> = sys.cpus().chunks(1).enumerate().map(|(chunk_idx, chunk)|{
+    let rows: Vec<Row> = snapshot.cpus.chunks(1).enumerate().map(|(chunk_idx, chunk)|{
         
         let mut cells = Vec::new();
 
-        for(_i, cpu) in chunk.iter().enumerate(){
+        for cpu in chunk {
             //let idx = chunk_idx * 2 + i;
             let idx = chunk_idx;
             cells.push(Cell::from(Span::raw(format!("CPU {}:", idx))));
-            cells.push(Cell::from(Span::styled(format!("{:.2}%", cpu.cpu_usage()),
-                            Style::default().fg(percent_color(cpu.cpu_usage() as f64)))));
+            cells.push(Cell::from(Span::styled(format!("{:.2}%", cpu.usage),
+                            Style::default().fg(percent_color(cpu.usage as f64)))));
         }
         
         Row::new(cells)
@@ -722,8 +2001,8 @@ fn cpu_info(sys: &sysinfo::System) -> Table{
 
     let footer = Row::new(vec![
         Cell::from(Span::raw("Average CPU%")),
-        Cell::from(Span::styled(format!("{:.2}%", sys.global_cpu_usage()),
-                    Style::default().fg(percent_color(sys.global_cpu_usage() as f64)))),
+        Cell::from(Span::styled(format!("{:.2}%", snapshot.global_cpu_usage),
+                    Style::default().fg(percent_color(snapshot.global_cpu_usage as f64)))),
     ]);
 
     // go back to see dimensions
@@ -742,200 +2021,1192 @@ fn cpu_info(sys: &sysinfo::System) -> Table{
                 .borders(Borders::ALL)).footer(footer)
 }
 
-fn process_list<'a>(sys: &'a sysinfo::System, state: &'a mut AppState) -> Table<'a> {
-    // Get processes to display
-    let pids = if state.frozen && state.cached_pids.is_some() {
-        // Use cached PIDs if frozen
-        state.cached_pids.as_ref().unwrap().clone()
-    } else {
-        // Otherwise get fresh process list and sort
-        let mut pids: Vec<Pid> = sys.processes().keys().copied().collect();
-        
-        // Sort based on selected sort mode
-        match state.proc_sort_mode {
-            SortMode::Cpu => {
-                pids.sort_by(|a, b| {
-                    let proc_a = sys.process(*a).unwrap();
-                    let proc_b = sys.process(*b).unwrap();
-                    proc_b.cpu_usage().partial_cmp(&proc_a.cpu_usage()).unwrap()
-                });
-            },
-            SortMode::Memory => {
-                pids.sort_by(|a, b| {
-                    let proc_a = sys.process(*a).unwrap();
-                    let proc_b = sys.process(*b).unwrap();
-                    proc_b.memory().cmp(&proc_a.memory())
-                });
-            },
-            SortMode::Pid => {
-                pids.sort();
-            }
+// the process tree as a table: branch guides and names, then the process metrics;
+// collapsed nodes show the CPU and memory of their whole subtree
+fn process_tree<'a>(snapshot: &'a Snapshot, state: &'a AppState) -> Table<'a> {
+    let total_mem = snapshot.memory.total as f64;
+
+    let rows: Vec<Row> = state.tree_rows
+        .iter()
+        .skip(state.scroll_offset)
+        .take(state.tree_page)
+        .map(|row| {
+            let node = row.node.borrow();
+            let info = snapshot.process(node.get_pid());
+
+            let marker = if !row.has_children {
+                "  "
+            } else if row.collapsed {
+                "▸ "
+            } else {
+                "▾ "
+            };
+            let name_style = if state.tree_tagged.contains(&node.get_key()) {
+                Style::default().fg(TREE_COLOR).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(PROCESS_NAME_COLOR)
+            };
+            let name = Line::from(vec![
+                Span::styled(row.guide.clone(), Style::default().fg(TREE_COLOR)),
+                Span::raw(marker),
+                Span::styled(node.get_name(), name_style),
+            ]);
+
+            let (cpu, memory, aggregate) = if row.collapsed {
+                let (cpu, memory) = subtree_usage(&row.node, snapshot);
+                (Some(cpu), Some(memory), "Σ")
+            } else {
+                (info.map(|p| p.cpu_usage), info.map(|p| p.memory), "")
+            };
+            let cpu_cell = match cpu {
+                Some(cpu) => Cell::from(Span::styled(
+                    format!("{}{:.2}%", aggregate, cpu),
+                    Style::default().fg(percent_color(cpu as f64)),
+                )),
+                None => Cell::from("-"),
+            };
+            let memory_cell = match memory {
+                Some(memory) => Cell::from(Span::styled(
+                    format!("{}{}", aggregate, bytes_to_human(memory)),
+                    Style::default().fg(percent_color(memory as f64 / total_mem * 100.0)),
+                )),
+                None => Cell::from("-"),
+            };
+            let state_cell = match info {
+                Some(_) if state.killed_pids.contains(&node.get_pid()) => Cell::from("Killed"),
+                Some(info) => Cell::from(info.status.clone()),
+                None => Cell::from("-"),
+            };
+
+            Row::new(vec![
+                Cell::from(name),
+                Cell::from(node.get_pid().to_string()),
+                cpu_cell,
+                memory_cell,
+                state_cell,
+            ])
+        })
+        .collect();
+
+    let header_style = Style::default()
+        .fg(HEADER_COLOR)
+        .add_modifier(Modifier::BOLD);
+
+    Table::new(rows, [
+        Constraint::Min(30),     // Tree
+        Constraint::Length(8),   // PID
+        Constraint::Length(10),  // CPU
+        Constraint::Length(13),  // Memory
+        Constraint::Length(10),  // State
+    ])
+    .header(Row::new(vec!["Process", "PID", "CPU Usage", "Memory Usage", "State"]).style(header_style))
+    .block(Block::default()
+        .title(Span::styled(
+            format!(
+                "Process Tree [{}]{}{} [←/→: Collapse/Expand]",
+                state.tree_rows.len(),
+                if state.tree_tagged.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [Tagged: {}]", state.tree_tagged.len())
+                },
+                state.filter_title()),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL))
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+    .column_spacing(1)
+}
+
+fn process_list<'a>(snapshot: &'a Snapshot, state: &'a mut AppState) -> Table<'a> {
+    // Get processes to display
+    let pids = match &state.cached_pids {
+        // Use cached PIDs if frozen
+        Some(cached) if state.frozen => cached.clone(),
+        // Otherwise get fresh process list and sort
+        _ => {
+            let mut procs: Vec<&ProcessInfo> = snapshot.processes
+                .iter()
+                .filter(|p| state.is_listed(p))
+                .collect();
+
+            // Sort based on selected sort mode, ties by PID so rows don't jump between refreshes
+            procs.sort_by(|a, b| {
+                state.proc_sort_order
+                    .apply(state.proc_sort_mode.compare(a, b))
+                    .then(a.pid.cmp(&b.pid))
+            });
+
+            let pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+
+            // Cache the sorted list
+            state.cached_pids = Some(pids.clone());
+
+            pids
+        }
+    };
+    
+    // Calculate visible range based on scroll position
+    let total = pids.len();
+    let start = state.proc_scroll_position;
+    let total_mem = snapshot.memory.total as f64;
+    
+    // Create rows only for visible processes with selection highlighting
+    let rows: Vec<Row> = pids
+        .iter()
+        .skip(start)
+        .take(state.proc_show_count)
+        .enumerate()
+        .filter_map(|(idx, pid)| {
+            snapshot.process(*pid).map(|proc| {
+                
+                // Highlight selected row
+                let style = if idx == state.proc_selected_index && state.mode == Mode::Proc {
+                    Style::default().bg(Color::Blue).fg(Color::White)
+                } else {
+                    Style::default()
+                };
+
+                let cells: Vec<Cell> = state.columns
+                    .visible()
+                    .map(|column| process_cell(column.id, proc, state, total_mem))
+                    .collect();
+
+                Row::new(cells).style(style)
+            })
+        })
+        .collect();
+
+
+    // Create table with title indicating status and function keys
+    let freeze_status = if state.frozen { " [FROZEN]" } else { "" };
+    let proc_sort_mode = state.proc_sort_mode.header();
+    
+    let f_key_info = if state.show_help {
+        ""  // If help panel is shown, don't crowd the title
+    } else {
+        " [H: Help]"  // Show minimal help when panel is hidden
+    };
+
+    // the members of a group are listed until Esc goes back to the groups
+    let group_title = match (state.group_by, &state.group_drill, &state.cgroup_drill) {
+        (Some(by), Some(key), _) => format!(" [{}: {} (Esc: Back)]", by.name(), key),
+        (_, _, Some(path)) => format!(" [Cgroup: {} (Esc: Back)]", path),
+        _ => String::new(),
+    };
+
+    let header_style = Style::default()
+        .fg(HEADER_COLOR)
+        .add_modifier(Modifier::BOLD);
+
+    let widths: Vec<Constraint> = state.columns
+        .visible()
+        .map(|column| column.id.constraint(column.width))
+        .collect();
+    // the sorted column's header carries the direction arrow
+    let headers: Vec<String> = state.columns
+        .visible()
+        .map(|column| {
+            if column.id == state.proc_sort_mode {
+                format!("{}{}", column.id.header(), state.proc_sort_order.arrow())
+            } else {
+                column.id.header().to_string()
+            }
+        })
+        .collect();
+    
+    Table::new(rows, widths)
+    .header(Row::new(headers).style(header_style.add_modifier(Modifier::BOLD)))
+    .block(Block::default()
+    .title(
+        Span::styled(
+            format!(
+                "Processes [{}] [Sort: {} {}{}]{}{}{}",
+                total,
+                proc_sort_mode,
+                state.proc_sort_order.arrow(),
+                freeze_status,
+                group_title,
+                state.filter_title(),
+                f_key_info),
+            Style::default().add_modifier(Modifier::BOLD))
+)
+    .borders(Borders::ALL))
+    .column_spacing(1)
+}
+
+// the process table while grouping: one row per user or command with summed usage
+fn group_list<'a>(snapshot: &Snapshot, state: &mut AppState, by: GroupBy) -> Table<'a> {
+    let groups = match &state.cached_groups {
+        Some(cached) if state.frozen => cached.clone(),
+        _ => {
+            let mut groups = aggregate::aggregate(snapshot.processes.iter().filter(|p| state.filter.matches(p)), by);
+            groups.sort_by(|a, b| {
+                state.proc_sort_order
+                    .apply(state.proc_sort_mode.compare_groups(a, b))
+                    .then(a.key.cmp(&b.key))
+            });
+            state.cached_groups = Some(groups.clone());
+            groups
+        }
+    };
+    let total_mem = snapshot.memory.total as f64;
+
+    let rows: Vec<Row> = groups
+        .iter()
+        .skip(state.proc_scroll_position)
+        .take(state.proc_show_count)
+        .enumerate()
+        .map(|(idx, group)| {
+            let style = if idx == state.proc_selected_index && state.mode == Mode::Proc {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default()
+            };
+            let mem_percent = group.memory as f64 / total_mem * 100.0;
+            Row::new(vec![
+                Cell::from(Span::styled(group.key.clone(), Style::default().fg(PROCESS_NAME_COLOR))),
+                Cell::from(group.processes.to_string()),
+                Cell::from(group.threads.to_string()),
+                Cell::from(Span::styled(
+                    format!("{:.2}%", group.cpu_usage),
+                    Style::default().fg(percent_color(group.cpu_usage as f64)),
+                )),
+                Cell::from(Span::styled(
+                    format!("{} ({:.1}%)", bytes_to_human(group.memory), mem_percent),
+                    Style::default().fg(percent_color(mem_percent)),
+                )),
+                Cell::from(Span::styled(
+                    format!("{}/s", bytes_to_human(group.disk_read_rate)),
+                    Style::default().fg(disk_color(group.disk_read_rate)),
+                )),
+                Cell::from(Span::styled(
+                    format!("{}/s", bytes_to_human(group.disk_write_rate)),
+                    Style::default().fg(disk_color(group.disk_write_rate)),
+                )),
+            ]).style(style)
+        })
+        .collect();
+
+    // the column the list is sorted by, when it has a counterpart here
+    let sorted = match state.proc_sort_mode {
+        ColumnId::Cpu => 3,
+        ColumnId::Memory | ColumnId::Res => 4,
+        ColumnId::Threads => 2,
+        ColumnId::DiskReadRate => 5,
+        ColumnId::DiskWriteRate => 6,
+        ColumnId::Name | ColumnId::Command | ColumnId::User | ColumnId::Uid | ColumnId::Owner => 0,
+        _ => 1,
+    };
+    let headers: Vec<String> = [by.name(), "Procs", "Threads", "CPU Usage", "Memory Usage", "Disk Read/s", "Disk Write/s"]
+        .iter()
+        .enumerate()
+        .map(|(idx, header)| {
+            if idx == sorted {
+                format!("{}{}", header, state.proc_sort_order.arrow())
+            } else {
+                header.to_string()
+            }
+        })
+        .collect();
+
+    Table::new(rows, [
+        Constraint::Min(20),
+        Constraint::Length(7),
+        Constraint::Length(9),
+        Constraint::Length(11),
+        Constraint::Length(20),
+        Constraint::Length(14),
+        Constraint::Length(14),
+    ])
+    .header(Row::new(headers).style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD)))
+    .block(Block::default()
+        .title(Span::styled(
+            format!(
+                "Processes by {} [{}]{}{} [Enter: Members  G: Regroup]",
+                by.name(),
+                groups.len(),
+                if state.frozen { " [FROZEN]" } else { "" },
+                state.filter_title()
+            ),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL))
+}
+
+// the cgroup of the marked process; read directly when the snapshot was taken before
+// cgroup paths were collected
+fn marked_cgroup(snapshot: &Snapshot, state: &AppState) -> Option<String> {
+    let pid = state.cgroup_pid?;
+    let proc = snapshot.process(pid)?;
+    proc.cgroup.clone().or_else(|| cgroup::process_cgroup(pid))
+}
+
+// the cgroup hierarchy in place of the process table, indented by depth, with the cgroup
+// of the marked process highlighted
+fn cgroup_list<'a>(snapshot: &Snapshot, state: &AppState) -> Table<'a> {
+    let marked = state.cgroup_pid.and_then(|pid| snapshot.process(pid));
+    let marked_path = marked_cgroup(snapshot, state);
+    let total_mem = snapshot.memory.total as f64;
+    let missing = || Cell::from("-");
+
+    let rows: Vec<Row> = snapshot.cgroups
+        .iter()
+        .skip(state.proc_scroll_position)
+        .take(state.proc_show_count)
+        .enumerate()
+        .map(|(idx, group): (usize, &CgroupInfo)| {
+            let is_marked = marked_path.as_deref() == Some(group.path.as_str());
+            let style = if idx == state.proc_selected_index && state.mode == Mode::Proc {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default()
+            };
+            let name = format!(
+                "{}{}{}",
+                "  ".repeat(group.depth),
+                group.name(),
+                if is_marked { " ◀" } else { "" }
+            );
+            let name_style = if is_marked {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(PROCESS_NAME_COLOR)
+            };
+            let memory = match (group.memory_current, group.memory_max) {
+                (Some(current), Some(max)) => {
+                    let percent = current as f64 / max as f64 * 100.0;
+                    Cell::from(Span::styled(
+                        format!("{} / {}", bytes_to_human(current), bytes_to_human(max)),
+                        Style::default().fg(percent_color(percent)),
+                    ))
+                }
+                (Some(current), None) => Cell::from(Span::styled(
+                    bytes_to_human(current),
+                    Style::default().fg(percent_color(current as f64 / total_mem * 100.0)),
+                )),
+                (None, _) => missing(),
+            };
+            let rate = |rate: Option<u64>| match rate {
+                Some(rate) => Cell::from(Span::styled(
+                    format!("{}/s", bytes_to_human(rate)),
+                    Style::default().fg(disk_color(rate)),
+                )),
+                None => missing(),
+            };
+            let pressure = |avg10: Option<f32>| avg10.map_or("-".to_string(), |avg| format!("{:.2}", avg));
+
+            Row::new(vec![
+                Cell::from(Span::styled(name, name_style)),
+                match group.cpu_usage {
+                    Some(usage) => Cell::from(Span::styled(
+                        format!("{:.2}%", usage),
+                        Style::default().fg(percent_color(usage as f64)),
+                    )),
+                    None => missing(),
+                },
+                memory,
+                rate(group.io_read_rate),
+                rate(group.io_write_rate),
+                group.pids_current.map_or_else(missing, |pids| Cell::from(pids.to_string())),
+                Cell::from(group.procs.to_string()),
+                Cell::from(format!(
+                    "{:>6} {:>6} {:>6}",
+                    pressure(group.pressure.cpu),
+                    pressure(group.pressure.memory),
+                    pressure(group.pressure.io)
+                )),
+            ]).style(style)
+        })
+        .collect();
+
+    let mark_title = match (state.cgroup_pid, marked) {
+        (Some(pid), Some(proc)) => format!(" [◀ PID {} {}]", pid, proc.name),
+        (Some(pid), None) => format!(" [PID {} has exited]", pid),
+        (None, _) => String::new(),
+    };
+    let title = if snapshot.cgroups.is_empty() {
+        " [no cgroup v2 hierarchy]".to_string()
+    } else {
+        String::new()
+    };
+
+    Table::new(rows, [
+        Constraint::Min(24),
+        Constraint::Length(9),
+        Constraint::Length(20),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(20),
+    ])
+    .header(Row::new(["Cgroup", "CPU", "Memory / Max", "IO Read/s", "IO Write/s", "Pids", "Procs", "PSI cpu/mem/io"])
+        .style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD)))
+    .block(Block::default()
+        .title(Span::styled(
+            format!(
+                "Cgroups [{}]{}{}{} [Enter: Processes  C: Close]",
+                snapshot.cgroups.len(),
+                if state.frozen { " [FROZEN]" } else { "" },
+                title,
+                mark_title
+            ),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL))
+    .column_spacing(1)
+}
+
+// builds the cell of one process list column
+fn process_cell<'a>(column: ColumnId, proc: &ProcessInfo, state: &AppState, total_mem: f64) -> Cell<'a> {
+    match column {
+        ColumnId::Pid => Cell::from(proc.pid.to_string()),
+        ColumnId::Name => Cell::from(Span::styled(
+            proc.name.clone(),
+            Style::default().fg(PROCESS_NAME_COLOR)
+        )),
+        ColumnId::Nice => Cell::from(proc.nice.to_string()),
+        ColumnId::Priority => Cell::from(proc.priority.to_string()),
+        ColumnId::State => {
+            // Check if the process is marked as killed
+            if state.killed_pids.contains(&proc.pid) {
+                Cell::from("Killed")
+            } else {
+                Cell::from(proc.status.clone())
+            }
+        }
+        // top and htop display raw cpu so using that
+        ColumnId::Cpu => Cell::from(Span::styled(
+            format!("{:.2}%", proc.cpu_usage),
+            Style::default().fg(percent_color(proc.cpu_usage as f64)),
+        )),
+        ColumnId::Memory => Cell::from(Span::styled(
+            bytes_to_human(proc.memory),
+            Style::default().fg(percent_color(proc.memory as f64 / total_mem * 100.0))
+        )),
+        ColumnId::StartTime => Cell::from(
+            Local.timestamp_opt(proc.start_time as i64, 0)
+                .single()
+                .map(|dt| dt.format("%H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".to_string())
+        ),
+        ColumnId::Affinity => Cell::from(
+            proc.affinity
+                .as_deref()
+                .map(sched::format_cpu_list)
+                .unwrap_or_else(|| "-".to_string())
+        ),
+        ColumnId::LastCpu => Cell::from(proc.last_cpu.map(|cpu| cpu.to_string()).unwrap_or_else(|| "-".to_string())),
+        ColumnId::Pss => Cell::from(proc.pss.map(bytes_to_human).unwrap_or_else(|| "-".to_string())),
+        ColumnId::Uss => Cell::from(proc.uss.map(bytes_to_human).unwrap_or_else(|| "-".to_string())),
+        ColumnId::Swap => Cell::from(bytes_to_human(proc.swap)),
+        ColumnId::Owner => Cell::from(proc.owner.as_ref().map_or_else(|| "-".to_string(), |owner| owner.label())),
+        ColumnId::CpuTime => Cell::from(ms_to_human(proc.cpu_time)),
+        ColumnId::DiskReadRate => Cell::from(Span::styled(
+            format!("{}/s", bytes_to_human(proc.disk_read_rate)),
+            Style::default().fg(disk_color(proc.disk_read_rate))
+        )),
+        ColumnId::DiskWriteRate => Cell::from(Span::styled(
+            format!("{}/s", bytes_to_human(proc.disk_write_rate)),
+            Style::default().fg(disk_color(proc.disk_write_rate))
+        )),
+        // totals grow for as long as the process lives, so they are not colored like rates
+        ColumnId::DiskRead => Cell::from(bytes_to_human(proc.disk_read_bytes)),
+        ColumnId::DiskWrite => Cell::from(bytes_to_human(proc.disk_written_bytes)),
+        ColumnId::Threads => Cell::from(Span::styled(
+            proc.thread_count.to_string(),
+            Style::default().fg(thread_color(proc.thread_count))
+        )),
+        ColumnId::User => Cell::from(
+            proc.user.clone()
+                .or_else(|| proc.uid.map(|uid| uid.to_string()))
+                .unwrap_or_else(|| "-".to_string())
+        ),
+        ColumnId::Uid => Cell::from(proc.uid.map_or("-".to_string(), |uid| uid.to_string())),
+        ColumnId::Ppid => Cell::from(proc.ppid.map_or("-".to_string(), |ppid| ppid.to_string())),
+        // kernel threads have no command line, so show the name in brackets like ps
+        ColumnId::Command => if proc.cmd.is_empty() {
+            Cell::from(format!("[{}]", proc.name))
+        } else {
+            Cell::from(proc.cmd.clone())
+        },
+        ColumnId::Virt => Cell::from(bytes_to_human(proc.virtual_memory)),
+        ColumnId::Res => Cell::from(Span::styled(
+            bytes_to_human(proc.memory),
+            Style::default().fg(percent_color(proc.memory as f64 / total_mem * 100.0))
+        )),
+        ColumnId::Shr => Cell::from(bytes_to_human(proc.shared_memory)),
+        ColumnId::Tty => Cell::from(proc.tty.clone().unwrap_or_else(|| "?".to_string())),
+        ColumnId::Session => Cell::from(proc.session.to_string()),
+        ColumnId::Pgrp => Cell::from(proc.pgrp.to_string()),
+        // a process without a class gets best-effort from its nice value
+        ColumnId::IoPriority => Cell::from(
+            proc.io_priority
+                .map(|io| io.effective(proc.nice).short())
+                .unwrap_or_else(|| "-".to_string())
+        ),
+    }
+}
+
+// overlay listing every column with its visibility and width
+// rendered with a TableState so the selected column stays in view on small terminals
+fn column_picker<'a>(state: &AppState, picker: &ColumnPicker) -> Table<'a> {
+    let rows: Vec<Row> = state.columns
+        .columns()
+        .iter()
+        .map(|column| {
+            Row::new(vec![
+                Cell::from(if column.visible { "[x]" } else { "[ ]" }),
+                Cell::from(column.id.header()),
+                Cell::from(column.width.to_string()),
+            ])
+        })
+        .collect();
+
+    let footer = match &picker.error {
+        Some(error) => Row::new(vec![Cell::from(""), Cell::from(Span::styled(error.clone(), Style::default().fg(RED)))]),
+        None => Row::new(vec![Cell::from(""), Cell::from("Space: Show/Hide  J/K: Move  ←/→: Width  Enter: Save  Esc: Close")]),
+    };
+
+    Table::new(rows, [
+        Constraint::Length(4),
+        Constraint::Fill(1),
+        Constraint::Length(6),
+    ])
+    .header(
+        Row::new(vec!["", "Column", "Width"])
+            .style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD))
+    )
+    .footer(footer)
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+    .block(Block::default()
+        .title(Span::styled("Columns", Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(BACKGROUND)))
+}
+
+// the signal menu; rendered with a TableState so the selection stays in view
+fn signal_menu<'a>(menu: &SignalMenu, snapshot: &Snapshot) -> Table<'a> {
+    let rows: Vec<Row> = menu.signals
+        .iter()
+        .map(|signal| {
+            Row::new(vec![
+                Cell::from(signal.number.to_string()),
+                Cell::from(signal.name.clone()),
+                Cell::from(signals::description(signal.number)),
+            ])
+        })
+        .collect();
+
+    Table::new(rows, [
+        Constraint::Length(3),
+        Constraint::Length(12),
+        Constraint::Fill(1),
+    ])
+    .header(
+        Row::new(vec!["#", "Signal", ""])
+            .style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD))
+    )
+    .footer(Row::new(vec![Cell::from(""), Cell::from("Enter: Send"), Cell::from("Esc: Cancel")]))
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+    .block(Block::default()
+        .title(Span::styled(
+            format!("Send Signal to {}", target_label(snapshot, &menu.targets[menu.scope])),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(BACKGROUND)))
+}
+
+// the processes the signal menu's current scope reaches, listed before anything is sent
+fn signal_preview<'a>(menu: &SignalMenu, snapshot: &Snapshot) -> Paragraph<'a> {
+    let target = &menu.targets[menu.scope];
+    let pids = target.pids();
+    let mut lines = Vec::new();
+    if pids.contains(&std::process::id()) {
+        lines.push(Line::from(Span::styled(
+            "Includes this task manager!",
+            Style::default().fg(RED).add_modifier(Modifier::BOLD),
+        )));
+    }
+    let members: Vec<String> = pids
+        .iter()
+        .map(|pid| match snapshot.process(*pid) {
+            Some(proc) => format!("{} {}", pid, proc.name),
+            None => pid.to_string(),
+        })
+        .collect();
+    lines.push(Line::from(members.join(", ")));
+
+    let scopes = if menu.targets.len() > 1 { " [Tab: Process/Group/Session]" } else { "" };
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::default()
+            .title(format!("Affected: {}{}", pids.len(), scopes))
+            .borders(Borders::ALL)
+            .style(Style::default().bg(BACKGROUND)))
+}
+
+// the scheduling policy dialog: one row per policy, the RT priority below
+fn sched_dialog<'a>(dialog: &SchedDialog, snapshot: &Snapshot) -> Table<'a> {
+    let rows: Vec<Row> = Policy::ALL
+        .iter()
+        .map(|policy| {
+            let current = if *policy == dialog.current.0 { "*" } else { "" };
+            Row::new(vec![
+                Cell::from(current),
+                Cell::from(policy.name()),
+                Cell::from(policy.description()),
+            ])
+        })
+        .collect();
+
+    let priority = if dialog.policy.is_realtime() {
+        format!("RT priority: {} (←/→, Shift: ±10)", dialog.priority)
+    } else {
+        "RT priority: - (real-time policies only)".to_string()
+    };
+    let current = if dialog.current.0.is_realtime() {
+        format!("{} priority {}", dialog.current.0.name(), dialog.current.1)
+    } else {
+        dialog.current.0.name().to_string()
+    };
+
+    Table::new(rows, [
+        Constraint::Length(1),
+        Constraint::Length(12),
+        Constraint::Fill(1),
+    ])
+    .header(
+        Row::new(vec![Cell::from(""), Cell::from("Current:"), Cell::from(current)])
+            .style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD))
+    )
+    .footer(Row::new(vec![Cell::from(""), Cell::from("Enter: Apply"), Cell::from(priority)]))
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+    .block(Block::default()
+        .title(Span::styled(
+            format!("Scheduling Policy of {}", target_label(snapshot, &dialog.target)),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(BACKGROUND)))
+}
+
+// the I/O priority dialog: one row per class, the level below
+fn io_dialog<'a>(dialog: &IoDialog, snapshot: &Snapshot) -> Table<'a> {
+    let effective = dialog.current.effective(dialog.nice);
+    let rows: Vec<Row> = IoClass::SETTABLE
+        .iter()
+        .map(|class| {
+            let current = if *class == effective.class { "*" } else { "" };
+            Row::new(vec![
+                Cell::from(current),
+                Cell::from(class.name()),
+                Cell::from(class.description()),
+            ])
+        })
+        .collect();
+
+    let level = if dialog.class.has_level() {
+        format!("Level: {} (←/→, 0 is highest)", dialog.level)
+    } else {
+        "Level: - (idle has no levels)".to_string()
+    };
+    let current = match dialog.current.class {
+        IoClass::None => format!("none, {} from nice {}", effective.short(), dialog.nice),
+        _ => dialog.current.short(),
+    };
+
+    Table::new(rows, [
+        Constraint::Length(1),
+        Constraint::Length(12),
+        Constraint::Fill(1),
+    ])
+    .header(
+        Row::new(vec![Cell::from(""), Cell::from("Current:"), Cell::from(current)])
+            .style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD))
+    )
+    .footer(Row::new(vec![Cell::from(""), Cell::from("Enter: Apply"), Cell::from(level)]))
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+    .block(Block::default()
+        .title(Span::styled(
+            format!("I/O Priority of {}", target_label(snapshot, &dialog.target)),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(BACKGROUND)))
+}
+
+// the affinity editor: a checkbox per CPU with its current usage
+fn affinity_editor<'a>(editor: &AffinityEditor, snapshot: &Snapshot) -> Table<'a> {
+    let rows: Vec<Row> = editor.cpus
+        .iter()
+        .enumerate()
+        .map(|(cpu, checked)| {
+            let usage = snapshot.cpus.get(cpu).map(|c| c.usage).unwrap_or_default();
+            Row::new(vec![
+                Cell::from(if *checked { "[x]" } else { "[ ]" }),
+                Cell::from(format!("CPU {}", cpu)),
+                Cell::from(Span::styled(
+                    format!("{:.1}%", usage),
+                    Style::default().fg(percent_color(usage as f64)),
+                )),
+            ])
+        })
+        .collect();
+
+    let footer = match &editor.error {
+        Some(error) => Row::new(vec![Cell::from(""), Cell::from(Span::styled(error.clone(), Style::default().fg(RED)))]),
+        None => Row::new(vec![Cell::from(""), Cell::from("Space: Toggle  A: All/None  Enter: Apply")]),
+    };
+
+    Table::new(rows, [
+        Constraint::Length(4),
+        Constraint::Fill(1),
+        Constraint::Length(7),
+    ])
+    .header(
+        Row::new(vec!["", "CPU", "Usage"])
+            .style(Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD))
+    )
+    .footer(footer)
+    .row_highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+    .block(Block::default()
+        .title(Span::styled(
+            format!("CPU Affinity of {}", target_label(snapshot, &editor.target)),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(BACKGROUND)))
+}
+
+// one "key  value" line of the detail view
+fn detail_line<'a>(key: &str, value: impl Into<String>) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(format!("{:<28}", key), Style::default().fg(HEADER_COLOR)),
+        Span::raw(value.into()),
+    ])
+}
+
+// a part of the details that could not be read, e.g. EACCES on another user's environ
+fn detail_error<'a>(what: &str, e: &procfs::ProcError) -> Line<'a> {
+    Line::from(Span::styled(format!("{}: {}", what, e), Style::default().fg(RED)))
+}
+
+fn detail_heading<'a>(text: impl Into<String>) -> Line<'a> {
+    Line::from(Span::styled(text.into(), Style::default().fg(TREE_COLOR).add_modifier(Modifier::BOLD)))
+}
+
+fn detail_status_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    let status = match &details.status {
+        Ok(status) => status,
+        Err(e) => return lines.push(detail_error("status", e)),
+    };
+    let ids = |ids: [u32; 4]| format!("{} / {} / {} / {}", ids[0], ids[1], ids[2], ids[3]);
+    let count = |count: Option<u64>| count.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string());
+
+    lines.push(detail_line("Name", status.name.clone()));
+    lines.push(detail_line("State", status.state.clone()));
+    lines.push(detail_line("PID / PPID / TGID", format!("{} / {} / {}", status.pid, status.ppid, status.tgid)));
+    lines.push(detail_line("Tracer PID", status.tracerpid.to_string()));
+    lines.push(detail_line("Threads", status.threads.to_string()));
+    lines.push(Line::default());
+    lines.push(detail_heading("Credentials (real / effective / saved / fs)"));
+    lines.push(detail_line("UID", ids([status.ruid, status.euid, status.suid, status.fuid])));
+    lines.push(detail_line("GID", ids([status.rgid, status.egid, status.sgid, status.fgid])));
+    let groups: Vec<String> = status.groups.iter().map(|g| g.to_string()).collect();
+    lines.push(detail_line("Supplementary groups", groups.join(" ")));
+    lines.push(Line::default());
+    lines.push(detail_heading("Context switches"));
+    lines.push(detail_line("Voluntary", count(status.voluntary_ctxt_switches)));
+    lines.push(detail_line("Involuntary", count(status.nonvoluntary_ctxt_switches)));
+    lines.push(Line::default());
+    lines.push(detail_heading("Capabilities"));
+    let mask = |mask: Option<u64>| mask.map(|m| format!("{:016x}", m)).unwrap_or_else(|| "-".to_string());
+    lines.push(detail_line("Effective", mask(Some(status.capeff))));
+    lines.push(detail_line("Permitted", mask(Some(status.capprm))));
+    lines.push(detail_line("Inheritable", mask(Some(status.capinh))));
+    lines.push(detail_line("Bounding", mask(status.capbnd)));
+    lines.push(detail_line("Ambient", mask(status.capamb)));
+    lines.push(detail_line("No new privileges", count(status.nonewprivs)));
+    lines.push(detail_line("Seccomp mode", status.seccomp.map(|m| m.to_string()).unwrap_or_else(|| "-".to_string())));
+    // a full bounding set means root; listing 41 names would say less than "all"
+    let effective = details::capability_names(status.capeff);
+    if status.capeff != 0 && Some(status.capeff) == status.capbnd {
+        lines.push(detail_line("Effective names", "all"));
+    } else if effective.is_empty() {
+        lines.push(detail_line("Effective names", "none"));
+    } else {
+        for (idx, name) in effective.into_iter().enumerate() {
+            lines.push(detail_line(if idx == 0 { "Effective names" } else { "" }, name));
+        }
+    }
+}
+
+fn detail_command_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    lines.push(detail_heading("Command line"));
+    match &details.cmdline {
+        Ok(args) if args.is_empty() => lines.push(Line::from("(none, kernel thread or zombie)")),
+        Ok(args) => lines.extend(args.iter().enumerate().map(|(idx, arg)| detail_line(&format!("argv[{}]", idx), arg.clone()))),
+        Err(e) => lines.push(detail_error("cmdline", e)),
+    }
+    lines.push(Line::default());
+    match &details.environ {
+        Ok(vars) => {
+            lines.push(detail_heading(format!("Environment ({} variables)", vars.len())));
+            lines.extend(vars.iter().map(|(name, value)| Line::from(vec![
+                Span::styled(name.clone(), Style::default().fg(HEADER_COLOR)),
+                Span::raw(format!("={}", value)),
+            ])));
         }
-        
-        // Cache the sorted list if not frozen
-        if !state.frozen || state.cached_pids.is_none() {
-            state.cached_pids = Some(pids.clone());
+        Err(e) => {
+            lines.push(detail_heading("Environment"));
+            lines.push(detail_error("environ", e));
         }
-    
-        pids
+    }
+}
+
+fn detail_files_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    for (name, link) in [("cwd", &details.cwd), ("exe", &details.exe), ("root", &details.root)] {
+        match link {
+            Ok(path) => lines.push(detail_line(name, path.display().to_string())),
+            Err(e) => lines.push(detail_error(name, e)),
+        }
+    }
+}
+
+// the open files tab: fd count against RLIMIT_NOFILE, the filter box, one line per fd
+fn detail_fd_lines<'a>(details: &ProcessDetails, filter: &str, editing: bool, lines: &mut Vec<Line<'a>>) {
+    let files = match &details.open_files {
+        Ok(files) => files,
+        Err(e) => return lines.push(detail_error("fd", e)),
     };
-    
-    // Get number of CPU cores for normalization
-    let _cpu_count = sys.cpus().len() as f32;
-    
-    // Calculate visible range based on scroll position
-    let total = pids.len();
-    let start = state.proc_scroll_position;
-    let _end = std::cmp::min(start + state.proc_show_count, total);
-    
-    // Create rows only for visible processes with selection highlighting
-    let rows: Vec<Row> = pids
+
+    let soft_limit = details.limits.as_ref().ok().map(|limits| &limits.max_open_files.soft_limit);
+    let count = match soft_limit {
+        Some(procfs::process::LimitValue::Value(limit)) if *limit > 0 => {
+            let percent = files.len() as f64 / *limit as f64 * 100.0;
+            Span::styled(
+                format!("{} open of {} allowed (soft RLIMIT_NOFILE, {:.1}%)", files.len(), limit, percent),
+                Style::default().fg(percent_color(percent)),
+            )
+        }
+        Some(procfs::process::LimitValue::Unlimited) => Span::raw(format!("{} open, no RLIMIT_NOFILE", files.len())),
+        _ => Span::raw(format!("{} open", files.len())),
+    };
+    lines.push(Line::from(vec![Span::styled(format!("{:<28}", "File descriptors"), Style::default().fg(HEADER_COLOR)), count]));
+
+    let needle = filter.to_lowercase();
+    let flags = |file: &details::OpenFile| file.flags.map(details::flag_names).unwrap_or_default();
+    let shown: Vec<&details::OpenFile> = files
         .iter()
-        .skip(start)
-        .take(state.proc_show_count)
-        .enumerate()
-        .filter_map(|(idx, pid)| {
-            sys.process(*pid).map(|proc| {
-                
-                // top and htop display raw cpu so using that
-                // let normalized_cpu = proc.cpu_usage() / cpu_count;
-                
-                let start_time_epoch = proc.start_time(); // seconds since epoch
-                let start_time_str = Local.timestamp_opt(start_time_epoch as i64, 0)
-                    .single()
-                    .map(|dt| dt.format("%H:%M:%S").to_string())
-                    .unwrap_or_else(|| "-".to_string());
+        .filter(|file| {
+            needle.is_empty()
+                || file.fd.to_string() == needle
+                || file.target.to_lowercase().contains(&needle)
+                || file.kind.name().contains(&needle)
+                || flags(file).to_lowercase().contains(&needle)
+        })
+        .collect();
 
+    let filter_line = if editing {
+        Line::from(vec![
+            Span::styled("Filter: ", Style::default().fg(YELLOW).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{}█", filter)),
+        ])
+    } else if !filter.is_empty() {
+        Line::from(Span::styled(
+            format!("Filter: {} ({} of {} shown, /: Edit)", filter, shown.len(), files.len()),
+            Style::default().fg(YELLOW),
+        ))
+    } else {
+        Line::from(Span::styled("/: Filter", Style::default().fg(Color::DarkGray)))
+    };
+    lines.push(filter_line);
+    lines.push(Line::default());
+
+    lines.push(Line::from(Span::styled(
+        format!("{:>5}  {:<7}{:<28}{:>12}  {}", "FD", "TYPE", "FLAGS", "POS", "TARGET"),
+        Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD),
+    )));
+    for file in shown {
+        let pos = file.pos.map(|pos| pos.to_string()).unwrap_or_else(|| "-".to_string());
+        lines.push(Line::from(format!(
+            "{:>5}  {:<7}{:<28}{:>12}  {}",
+            file.fd,
+            file.kind.name(),
+            flags(file),
+            pos,
+            file.target
+        )));
+    }
+}
 
-                let cpu_time_ms = proc.accumulated_cpu_time();
+// the memory map tab: totals per kind of mapping, then the mappings or files
+fn detail_map_lines<'a>(view: &DetailView, lines: &mut Vec<Line<'a>>) {
+    let maps = match &view.maps {
+        Some(Ok(maps)) => maps,
+        Some(Err(e)) => return lines.push(detail_error("smaps", e)),
+        None => return lines.push(Line::from("Reading smaps...")),
+    };
+    let row_format = |range: &str, perms: &str, values: [String; 8], path: &str| {
+        format!(
+            "{:<27}{:<6}{:>11}{:>11}{:>11}{:>11}{:>11}{:>11}{:>11}{:>11}  {}",
+            range, perms, values[0], values[1], values[2], values[3], values[4], values[5], values[6], values[7], path
+        )
+    };
+    let values = |size: u64, usage: &details::MapUsage| {
+        [
+            size, usage.rss, usage.pss, usage.shared_clean, usage.shared_dirty,
+            usage.private_clean, usage.private_dirty, usage.swap,
+        ]
+        .map(bytes_to_human)
+    };
 
-                let cpu_time_str = ms_to_human(cpu_time_ms);
- 
-                let prio = procfs::process::Process::new(pid.as_u32() as i32)
-                    .and_then(|p| p.stat())
-                    .map(|stat| stat.priority)
-                    .unwrap_or_default();
-
-                let disk_usage = proc.disk_usage();
-                let disk_read_str = bytes_to_human(disk_usage.total_read_bytes);
-                let disk_write_str = bytes_to_human(disk_usage.total_written_bytes);
-                let thread_count = std::fs::read_dir(format!("/proc/{}/task", pid.as_u32()))
-                    .map(|dir| dir.count())
-                    .unwrap_or(0);
-
-                // Check if the process is marked as killed
-                let status = if state.killed_pids.contains(pid) {
-                    "Killed".to_string()
-                } else {
-                    format!("{}", proc.status())
-                };
+    // totals, split by what backs the memory
+    lines.push(Line::from(Span::styled(
+        row_format("", "", ["Size", "RSS", "PSS", "Shr Clean", "Shr Dirty", "Prv Clean", "Prv Dirty", "Swap"].map(String::from), ""),
+        Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD),
+    )));
+    let mut total = (0, details::MapUsage::default());
+    for category in details::MapCategory::ALL {
+        let mut sum = (0, details::MapUsage::default());
+        for map in maps.iter().filter(|map| map.category == category) {
+            sum.0 += map.end - map.start;
+            sum.1.add(&map.usage);
+        }
+        total.0 += sum.0;
+        total.1.add(&sum.1);
+        lines.push(Line::from(row_format(category.name(), "", values(sum.0, &sum.1), "")));
+    }
+    lines.push(Line::from(Span::styled(
+        row_format(&format!("Total ({} mappings)", maps.len()), "", values(total.0, &total.1), ""),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::default());
+
+    lines.push(Line::from(Span::styled(
+        format!(
+            "S: Sort ({} {})  I: Invert  G: {}",
+            view.map_sort.label(),
+            view.map_order.arrow(),
+            if view.map_grouped { "Show Mappings" } else { "Group by File" }
+        ),
+        Style::default().fg(Color::DarkGray),
+    )));
+    let range = if view.map_grouped { "Mappings" } else { "Address" };
+    lines.push(Line::from(Span::styled(
+        row_format(range, "Perm", ["Size", "RSS", "PSS", "Shr Clean", "Shr Dirty", "Prv Clean", "Prv Dirty", "Swap"].map(String::from), "Path"),
+        Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD),
+    )));
+    for row in view.map_rows() {
+        lines.push(Line::from(row_format(&row.range, &row.perms, values(row.size, &row.usage), &row.path)));
+    }
+}
 
-                
-                // Highlight selected row
-                let style = if idx == state.proc_selected_index && state.mode == Mode::Proc {
-                    Style::default().bg(Color::Blue).fg(Color::White)
+fn detail_limits_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    let limits = match &details.limits {
+        Ok(limits) => limits,
+        Err(e) => return lines.push(detail_error("limits", e)),
+    };
+    let value = |value: &procfs::process::LimitValue| match value {
+        procfs::process::LimitValue::Unlimited => "unlimited".to_string(),
+        procfs::process::LimitValue::Value(v) => v.to_string(),
+    };
+    lines.push(Line::from(Span::styled(
+        format!("{:<28}{:<22}{:<22}{}", "Limit", "Soft", "Hard", "Units"),
+        Style::default().fg(HEADER_COLOR).add_modifier(Modifier::BOLD),
+    )));
+    for (name, limit, unit) in details::limit_rows(limits) {
+        lines.push(Line::from(format!(
+            "{:<28}{:<22}{:<22}{}",
+            name,
+            value(&limit.soft_limit),
+            value(&limit.hard_limit),
+            unit
+        )));
+    }
+}
+
+fn detail_cgroup_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    match &details.cgroups {
+        Ok(cgroups) => {
+            for cgroup in cgroups {
+                // cgroup v2 is hierarchy 0 with no controllers
+                let key = if cgroup.hierarchy == 0 && cgroup.controllers.is_empty() {
+                    "v2 (unified)".to_string()
                 } else {
-                    Style::default()
+                    format!("{}: {}", cgroup.hierarchy, cgroup.controllers.join(","))
                 };
+                lines.push(detail_line(&key, cgroup.pathname.clone()));
+            }
+        }
+        Err(e) => lines.push(detail_error("cgroup", e)),
+    }
+}
 
-                let total_mem = sys.total_memory() as f64;
-                Row::new(vec![
-                    Cell::from(pid.to_string()),
-                    Cell::from(Span::styled(
-                        proc.name().to_string_lossy().to_string(),
-                        Style::default().fg(PROCESS_NAME_COLOR)
-                    )),
-                    
-                    Cell::from(unsafe { getpriority(PRIO_PROCESS, pid.as_u32()) }.to_string()),
-                    Cell::from(prio.to_string()),
-                    Cell::from(status), // Display custom status here
-                    Cell::from(Span::styled(
-                        format!("{:.2}%", proc.cpu_usage()),
-                        Style::default().fg(percent_color(proc.cpu_usage() as f64)),
-                    )),
-                    Cell::from(Span::styled(
-                        format!("{}", (bytes_to_human(proc.memory()))),
-                        Style::default().fg(percent_color(proc.memory() as f64 / total_mem * 100.0))
-                    )),
-                    Cell::from(start_time_str),
-                    Cell::from(cpu_time_str),
-                    Cell::from(Span::styled(
-                        disk_read_str,
-                        Style::default().fg(disk_color(disk_usage.total_read_bytes))
-                    )),
-                    Cell::from(Span::styled(
-                        disk_write_str,
-                        Style::default().fg(disk_color(disk_usage.total_written_bytes))
-                    )),
-                    Cell::from(Span::styled(
-                        thread_count.to_string(),
-                        Style::default().fg(thread_color(thread_count))
-                    ))
-                ]).style(style)
-            })
-        })
-        .collect();
+fn detail_namespace_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    match &details.namespaces {
+        Ok(namespaces) => {
+            for ns in namespaces {
+                let name = ns.ns_type.to_string_lossy();
+                lines.push(detail_line(&name, format!("{}:[{}]", name, ns.identifier)));
+            }
+        }
+        Err(e) => lines.push(detail_error("ns", e)),
+    }
+}
 
+fn detail_oom_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    match &details.oom_score {
+        Ok(score) => lines.push(detail_line("OOM score", format!("{} (0-1000, highest is killed first)", score))),
+        Err(e) => lines.push(detail_error("oom_score", e)),
+    }
+    match &details.oom_score_adj {
+        Ok(adj) => lines.push(detail_line("OOM score adjustment", format!("{} (-1000 never kills)", adj))),
+        Err(e) => lines.push(detail_error("oom_score_adj", e)),
+    }
+}
 
-    // Create table with title indicating status and function keys
-    let freeze_status = if state.frozen { " [FROZEN]" } else { "" };
-    let proc_sort_mode = match state.proc_sort_mode {
-        SortMode::Cpu => "CPU",
-        SortMode::Memory => "MEM",
-        SortMode::Pid => "PID",
-    };
-    
-    let f_key_info = if state.show_help {
-        ""  // If help panel is shown, don't crowd the title
-    } else {
-        " [H: Help]"  // Show minimal help when panel is hidden
+fn detail_io_lines<'a>(details: &ProcessDetails, lines: &mut Vec<Line<'a>>) {
+    let io = match &details.io {
+        Ok(io) => io,
+        Err(e) => return lines.push(detail_error("io", e)),
     };
+    let bytes = |b: u64| format!("{} ({} bytes)", bytes_to_human(b), b);
+    lines.push(detail_line("Characters read", bytes(io.rchar)));
+    lines.push(detail_line("Characters written", bytes(io.wchar)));
+    lines.push(detail_line("Read syscalls", io.syscr.to_string()));
+    lines.push(detail_line("Write syscalls", io.syscw.to_string()));
+    lines.push(detail_line("Bytes read from storage", bytes(io.read_bytes)));
+    lines.push(detail_line("Bytes written to storage", bytes(io.write_bytes)));
+    lines.push(detail_line("Cancelled write bytes", bytes(io.cancelled_write_bytes)));
+}
 
-    let header_style = Style::default()
-        .fg(HEADER_COLOR)
-        .add_modifier(Modifier::BOLD);
-    
-    Table::new(rows, [
-        Constraint::Length(8),       // PID column width
-        Constraint::Percentage(30),  // Process name column width
-        Constraint::Length(5),       // Nice values column width 
-        Constraint::Length(10),       // Priority column width
-        Constraint::Length(10),      // State column width
-        Constraint::Length(12),      // CPU usage column width
-        Constraint::Length(15),      // Memory usage column width
-        Constraint::Length(15),      // Start time column width
-        Constraint::Length(10),      
-        Constraint::Length(14),
-        Constraint::Length(14),
-        Constraint::Length(8),
-    ])
-    .header(Row::new(vec![
-        "PID".to_string(),
-        "Process Name".to_string(),
-        "NI".to_string(),
-        "Priority".to_string(),
-        "State".to_string(),       
-        "CPU Usage".to_string(),
-        "Memory Usage".to_string(),
-        "Start Time".to_string(),
-        "CPU Time".to_string(),
-        "Disk Read".to_string(),
-        "Disk Write".to_string(),
-        "Threads".to_string(),
-    ]).style(header_style.add_modifier(Modifier::BOLD)))
-    .block(Block::default()
-    .title(
-        Span::styled(
-            format!(
-                "Processes [{}] [Sort: {}{}]{}",
-                total,
-                proc_sort_mode,
-                freeze_status,
-                f_key_info),
-            Style::default().add_modifier(Modifier::BOLD))
-)
-    .borders(Borders::ALL))
-    .column_spacing(1)
+// the full-screen detail view: tabs on top, the selected tab below
+fn draw_detail_view(frame: &mut Frame, view: &mut DetailView, area: Rect) {
+    let block = Block::default()
+        .title(Span::styled(
+            format!("Details of PID {} ({})", view.pid, view.name),
+            Style::default().add_modifier(Modifier::BOLD)))
+        .title_bottom(" ←/→/0-9: Tab  ↑/↓/PgUp/PgDn: Scroll  Esc: Back ")
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [tabs_area, body] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Fill(1),
+    ]).areas(inner);
+    let selected = DetailTab::ALL.iter().position(|tab| *tab == view.tab);
+    let tabs = Tabs::new(DetailTab::ALL.iter().map(|tab| tab.title()))
+        .select(selected)
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .block(Block::default().borders(Borders::BOTTOM));
+    frame.render_widget(tabs, tabs_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    match &view.details {
+        Ok(details) => match view.tab {
+            DetailTab::Status => detail_status_lines(details, &mut lines),
+            DetailTab::Command => detail_command_lines(details, &mut lines),
+            DetailTab::Files => detail_files_lines(details, &mut lines),
+            DetailTab::Fds => detail_fd_lines(details, &view.filter, view.editing_filter, &mut lines),
+            DetailTab::Maps => detail_map_lines(view, &mut lines),
+            DetailTab::Limits => detail_limits_lines(details, &mut lines),
+            DetailTab::Cgroup => detail_cgroup_lines(details, &mut lines),
+            DetailTab::Namespaces => detail_namespace_lines(details, &mut lines),
+            DetailTab::Oom => detail_oom_lines(details, &mut lines),
+            DetailTab::Io => detail_io_lines(details, &mut lines),
+        },
+        Err(_) => lines.push(Line::from(Span::styled("The process has exited", Style::default().fg(RED)))),
+    }
+
+    // stop scrolling once the last line is at the bottom
+    view.scroll = view.scroll.min(lines.len().saturating_sub(body.height as usize) as u16);
+    frame.render_widget(Paragraph::new(lines).scroll((view.scroll, 0)), body);
+}
+
+fn draw_signal_menu(frame: &mut Frame, state: &AppState, snapshot: &Snapshot, area: Rect) {
+    if let Some(menu) = &state.signal_menu {
+        let popup = centered_rect(64, area.height.saturating_sub(4), area);
+        let [list, preview] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(7),
+        ]).areas(popup);
+        let mut table_state = TableState::default().with_selected(Some(menu.selected));
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(signal_menu(menu, snapshot), list, &mut table_state);
+        frame.render_widget(signal_preview(menu, snapshot), preview);
+    }
+    if let Some(dialog) = &state.sched_dialog {
+        let popup = centered_rect(64, Policy::ALL.len() as u16 + 4, area);
+        let selected = Policy::ALL.iter().position(|p| *p == dialog.policy);
+        let mut table_state = TableState::default().with_selected(selected);
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(sched_dialog(dialog, snapshot), popup, &mut table_state);
+    }
+    if let Some(dialog) = &state.io_dialog {
+        let popup = centered_rect(64, IoClass::SETTABLE.len() as u16 + 4, area);
+        let selected = IoClass::SETTABLE.iter().position(|c| *c == dialog.class);
+        let mut table_state = TableState::default().with_selected(selected);
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(io_dialog(dialog, snapshot), popup, &mut table_state);
+    }
+    if let Some(editor) = &state.affinity_editor {
+        let popup = centered_rect(56, editor.cpus.len() as u16 + 4, area);
+        let mut table_state = TableState::default().with_selected(Some(editor.selected));
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(affinity_editor(editor, snapshot), popup, &mut table_state);
+    }
+}
+
+// the bottom line: a pending confirmation, else the last result while it is fresh
+fn status_bar<'a>(state: &AppState) -> Paragraph<'a> {
+    if let Some(prompt) = &state.nice_prompt {
+        let mut spans = vec![
+            Span::styled(prompt.prompt.clone(), Style::default().fg(YELLOW).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{}█", prompt.input)),
+        ];
+        if let Some(error) = &prompt.error {
+            spans.push(Span::styled(format!("  {}", error), Style::default().fg(RED)));
+        }
+        return Paragraph::new(Line::from(spans));
+    }
+    if let Some(pending) = &state.pending_signal {
+        return Paragraph::new(Span::styled(
+            pending.prompt.clone(),
+            Style::default().fg(YELLOW).add_modifier(Modifier::BOLD),
+        ));
+    }
+    match &state.status {
+        Some(status) if status.since.elapsed() < STATUS_TIMEOUT => {
+            let color = if status.error { RED } else { LIGHT_GREEN };
+            Paragraph::new(Span::styled(status.text.clone(), Style::default().fg(color)))
+        }
+        _ => Paragraph::new(""),
+    }
+}
+
+// index of the table column whose header is at (x, y), laid out the way Table lays out
+// bordered tables with a column spacing of 1
+fn header_column_at(area: Rect, widths: &[Constraint], x: u16, y: u16) -> Option<usize> {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    if y != inner.y || inner.width == 0 {
+        return None;
+    }
+    Layout::horizontal(widths.to_vec())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(inner)
+        .iter()
+        .position(|rect| x >= rect.x && x < rect.x + rect.width)
+}
+
+// a rectangle of the given size centered in `area`
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
 
 // Helper function to format bytes into human-readable units
@@ -952,20 +3223,18 @@ fn bytes_to_human(bytes: u64) -> String {
     format!("{:.1} {}", size, UNITS[unit_idx])
 }
 
-fn get_overall_process_data<'a>(sys: &'a sysinfo::System, app: &'a mut AppState)-> Table<'a> {
+fn get_overall_process_data<'a>(snapshot: &'a Snapshot, app: &'a mut AppState)-> Table<'a> {
     
     let mut name = String::new();
     let mut memory = 0;
 
-    if let Some(process) = sys.process(app.thread_process_pid)
+    if let Some(process) = snapshot.process(app.thread_process_pid)
     {
-        name = process.name().to_string_lossy().into_owned();
-        memory = process.memory();
+        name = process.name.clone();
+        memory = process.memory;
     }
     
-    let thread_count = fs::read_dir(format!("/proc/{}/task", app.thread_process_pid))
-    .map(|dir| dir.count())
-    .unwrap_or(0);
+    let thread_count = snapshot.threads.len();
 
     app.latest_thread_count = thread_count;
 
@@ -987,8 +3256,8 @@ fn get_overall_process_data<'a>(sys: &'a sysinfo::System, app: &'a mut AppState)
         Row::new(vec![
             Cell::from("Memory".to_string()),
             Cell::from(Span::styled(
-                format!("{}", (bytes_to_human(memory))),
-                Style::default().fg(percent_color(memory as f64 / sys.total_memory() as f64 * 100.0))
+                bytes_to_human(memory),
+                Style::default().fg(percent_color(memory as f64 / snapshot.memory.total as f64 * 100.0))
             )),
         ]),
     ];
@@ -1013,87 +3282,37 @@ fn ms_to_human(ms: u64) -> String {
     format!("{:02}:{:02}:{:02}", hours, mins % 60, secs % 60)
 }
 
-fn get_thread_info(state: & mut AppState) -> ProcResult<Vec<ThreadInfo>> {
-    
-    let pid = state.thread_process_pid.as_u32() as i32;
-    let process = Process::new(pid)?;
-    let tasks = process.tasks()?;
-    let now = Instant::now();
-    let clock_ticks = procfs::ticks_per_second() as f64;
-            
-
-    tasks
-        .map(|task_result| {
-            let task = task_result?; // Propagates task enumeration errors
-            let stat = task.stat()?; // Propagates stat parsing errors
-            let total_cpu= stat.utime + stat.stime;
-            
-            let mut cpu_percent= 0.0;
-
-            if let Some(prev) = state.thread_samples.get(&task.tid) {
-                let elapsed = now.duration_since(prev.last_seen).as_secs_f64();
-                if elapsed > 0.0 {
-                        let delta_cpu = (total_cpu - prev.last_cpu_time) as f64 / clock_ticks;
-                        cpu_percent = 100.0 * (delta_cpu / elapsed);
-                }
-            }
-
-            state.thread_samples.insert(task.tid, ThreadSample {
-                last_cpu_time: total_cpu,
-                last_seen: now,
-            });
-
-
-            Ok(ThreadInfo { // Wrap in Ok()
-                tid: task.tid as u32,
-                name: stat.comm,
-                state: stat.state.to_string(),
-                cpu: cpu_percent,
-                priority: stat.priority,
-            })
-    })
-    .collect()
-}
-
+const THREAD_COLUMN_WIDTHS: [Constraint; 5] = [
+    Constraint::Length(6),   // TID
+    Constraint::Length(16),  // Name
+    Constraint::Length(6),   // State
+    Constraint::Length(8),   // CPU
+    Constraint::Length(5),   // Priority
+];
 
-fn thread_info_to_table<'a>(state: &'a mut AppState) -> Table<'a>{
-    // Convert clock ticks to seconds (Linux default is 100 ticks/sec)
-  
-
-    let threads = if state.frozen && state.cached_threads.is_some() {
+fn thread_info_to_table<'a>(snapshot: &'a Snapshot, state: &'a mut AppState) -> Table<'a>{
+    let threads = match &state.cached_threads {
         // used cached threads if frozen
-        state.cached_threads.as_ref().unwrap().clone()
-    } else{ 
-        let mut threads = get_thread_info(state).unwrap_or_default();
-
-        // Sort based on selected sort mode
-        match state.thread_sort_mode {
-            SortMode::Cpu => {
-                threads.sort_by(|a, b| b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal));
-            },
-            SortMode::Pid => {
-                threads.sort_by(|a, b| a.tid.cmp(&b.tid));
-            }
-            SortMode::Memory => {
-                threads.sort_by(|a, b| a.priority.cmp(&b.priority));
-            }
-        }
+        Some(cached) if state.frozen => cached.clone(),
+        _ => {
+            let mut threads = snapshot.threads.clone();
+
+            // Sort based on selected sort mode
+            threads.sort_by(|a, b| {
+                state.thread_sort_order
+                    .apply(state.thread_sort_mode.compare(a, b))
+                    .then(a.tid.cmp(&b.tid))
+            });
 
-        // Cache the sorted list if not frozen
-        if !state.frozen || state.cached_threads.is_none() {
+            // Cache the sorted list
             state.cached_threads = Some(threads.clone());
-        }
-    
-        threads
-
-        };
 
-    let sort_mode = match state.thread_sort_mode {
-        SortMode::Cpu => "CPU",
-        SortMode::Pid => "TID",
-        SortMode::Memory => "PRIO",
+            threads
+        }
     };
 
+    let sort_mode = state.thread_sort_mode.label();
+
     let header_style = Style::default()
         .fg(HEADER_COLOR)
         .add_modifier(Modifier::BOLD);
@@ -1129,21 +3348,27 @@ fn thread_info_to_table<'a>(state: &'a mut AppState) -> Table<'a>{
         ]).style(style)
         }).collect();
 
-        Table::new(rows, [
-            Constraint::Length(6),   // TID
-            Constraint::Length(16),  // Name
-            Constraint::Length(6),   // State
-            Constraint::Length(8),   // CPU
-            Constraint::Length(5),   // Priority
-        ])
+        let headers: Vec<String> = ThreadSortMode::ALL
+            .iter()
+            .zip(["TID", "Name", "State", "CPU%", "Prio"])
+            .map(|(mode, header)| {
+                if *mode == state.thread_sort_mode {
+                    format!("{}{}", header, state.thread_sort_order.arrow())
+                } else {
+                    header.to_string()
+                }
+            })
+            .collect();
+
+        Table::new(rows, THREAD_COLUMN_WIDTHS)
         .header(
-            Row::new(vec!["TID", "Name", "State", "CPU%", "Prio"])
+            Row::new(headers)
                 .style(header_style)
         )
         .block(Block::default()
         .title(
                 Span::styled(
-                    format!("Thread Data [Sort: {}]", sort_mode),
+                    format!("Thread Data [Sort: {} {}]", sort_mode, state.thread_sort_order.arrow()),
                     Style::default().add_modifier(Modifier::BOLD))
         )
         .borders(Borders::ALL))
@@ -1157,7 +3382,7 @@ fn calculate_graph_x_ticks(area_width: u16)-> usize {
     max_x_ticks as usize
 }
 
-fn calculate_x_bounds(data: &Vec<(f64, f64)>, x_ticks: usize)-> (f64, f64) {
+fn calculate_x_bounds(data: &[(f64, f64)], x_ticks: usize)-> (f64, f64) {
     
     let x_start = data
         .get(data.len().saturating_sub(x_ticks))
@@ -1173,10 +3398,10 @@ fn calculate_x_bounds(data: &Vec<(f64, f64)>, x_ticks: usize)-> (f64, f64) {
     (x_start, x_end)
 }
 
-fn get_cpu_graph<'a>(sys: &'a sysinfo::System, app: &'a mut AppState, area: Rect) ->Chart<'a>{
+fn get_cpu_graph<'a>(snapshot: &'a Snapshot, app: &'a mut AppState, area: Rect) ->Chart<'a>{
     
     let new_x = app.cpu_graph.last().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-    let new_y = sys.global_cpu_usage() as f64;
+    let new_y = snapshot.global_cpu_usage as f64;
 
     app.cpu_graph.push((new_x, new_y));
     let percent = new_y;
@@ -1227,62 +3452,16 @@ struct MemoryGauges<'a> {
     free_mem: f64,
     block: Block<'a>,
 }
-/// Returns (total, used, available, cached, free) in GiB, calculated exactly like btop
-fn get_btop_memory_stats() -> (f64, f64, f64, f64, f64) {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-
-    let mut memtotal = 0.0;
-    let mut memfree = 0.0;
-    let mut memavailable = 0.0;
-    let mut cached = 0.0;
-    let mut sreclaimable = 0.0;
-    let mut shmem = 0.0;
-
-    let file = File::open("/proc/meminfo").unwrap();
-    let reader = BufReader::new(file);
-
-    for line in reader.lines().flatten() {
-        if line.starts_with("MemTotal:") {
-            memtotal = line.split_whitespace().nth(1).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-        } else if line.starts_with("MemFree:") {
-            memfree = line.split_whitespace().nth(1).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-        } else if line.starts_with("MemAvailable:") {
-            memavailable = line.split_whitespace().nth(1).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-        } else if line.starts_with("Cached:") && !line.contains("SwapCached") {
-            cached = line.split_whitespace().nth(1).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-        } else if line.starts_with("SReclaimable:") {
-            sreclaimable = line.split_whitespace().nth(1).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-        } else if line.starts_with("Shmem:") {
-            shmem = line.split_whitespace().nth(1).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-        }
-    }
-
-    // btop's formulas:
-    let used = memtotal - memavailable;
-    let cached_btop = cached + sreclaimable - shmem;
-
-    // Convert from KiB to GiB (1 GiB = 1024^2 KiB)
-    let kb_to_gib = 1024.0 * 1024.0;
-    (
-        memtotal / kb_to_gib,         // total
-        used / kb_to_gib,             // used
-        memavailable / kb_to_gib,     // available
-        cached_btop / kb_to_gib,      // cached
-        memfree / kb_to_gib           // free
-    )
-}
-     
-
 impl<'a> MemoryGauges<'a> {
-    fn new(_sys: &sysinfo::System) -> Self {
-        let (total_mem, used_mem, available_mem, cached_mem, free_mem) = get_btop_memory_stats();
+    fn new(memory: &MemoryStats) -> Self {
+        // Convert from bytes to GiB
+        let to_gib = |bytes: u64| bytes as f64 / 1_073_741_824.0;
         Self {
-            total_mem,
-            used_mem,
-            available_mem,
-            cached_mem,
-            free_mem,
+            total_mem: to_gib(memory.total),
+            used_mem: to_gib(memory.used),
+            available_mem: to_gib(memory.available),
+            cached_mem: to_gib(memory.cached),
+            free_mem: to_gib(memory.free),
             block: Block::default()
                 .title("Mem")
                 .borders(Borders::ALL)
@@ -1345,19 +3524,19 @@ impl<'a> Widget for MemoryGauges<'a> {
     }
 }
 
-fn memory_gauges<'a>(sys: &sysinfo::System) -> MemoryGauges {
-    MemoryGauges::new(sys)
+fn memory_gauges(snapshot: &Snapshot) -> MemoryGauges<'_> {
+    MemoryGauges::new(&snapshot.memory)
 }
 struct DiskGauges<'a> {
-    sys: &'a sysinfo::System,
+    snapshot: &'a Snapshot,
     block: Block<'a>,
 }
 
 
 impl<'a> DiskGauges<'a> {
-    fn new(sys: &'a sysinfo::System) -> Self {
+    fn new(snapshot: &'a Snapshot) -> Self {
         Self {
-            sys,
+            snapshot,
             block: Block::default()
                 .title("Disks")
                 // .border_style(Color::Rgb((20), (30), (40)))
@@ -1436,13 +3615,12 @@ impl<'a> Widget for DiskGauges<'a> {
         const DISK_ENTRY_HEIGHT: u16 = 3; // Title + Used + Free
         
         // Calculate how many disks we show
-        let disks = sysinfo::Disks::new_with_refreshed_list();
         let mut y_offset = inner.y;
         
         // First render root disk (if exists)
-        if let Some(disk) = disks.iter().find(|d| d.mount_point().to_string_lossy() == "/") {
-            let total = disk.total_space() as f64 / 1_073_741_824.0; // GiB
-            let free = disk.available_space() as f64 / 1_073_741_824.0;
+        if let Some(disk) = self.snapshot.disks.iter().find(|d| d.mount_point == "/") {
+            let total = disk.total_space as f64 / 1_073_741_824.0; // GiB
+            let free = disk.available_space as f64 / 1_073_741_824.0;
             let used = total - free;
             let used_percent = (used / total) * 100.0;
             let free_percent = 100.0 - used_percent;
@@ -1506,9 +3684,9 @@ impl<'a> Widget for DiskGauges<'a> {
         }
         
         // Then render swap if it exists
-        if self.sys.total_swap() > 0 {
-            let total_swap = self.sys.total_swap() as f64 / 1_073_741_824.0; // GiB
-            let used_swap = self.sys.used_swap() as f64 / 1_073_741_824.0;
+        if self.snapshot.memory.swap_total > 0 {
+            let total_swap = self.snapshot.memory.swap_total as f64 / 1_073_741_824.0; // GiB
+            let used_swap = self.snapshot.memory.swap_used as f64 / 1_073_741_824.0;
             let used_percent = if total_swap > 0.0 { (used_swap / total_swap) * 100.0 } else { 0.0 };
             let free_percent = 100.0 - used_percent;
             
@@ -1566,11 +3744,11 @@ impl<'a> Widget for DiskGauges<'a> {
     }
 }
 
-fn disk_gauges(sys: &sysinfo::System) -> DiskGauges {
-    DiskGauges::new(sys)
+fn disk_gauges(snapshot: &Snapshot) -> DiskGauges<'_> {
+    DiskGauges::new(snapshot)
 }
 
-fn draw_ui(sys: &sysinfo::System, state: &mut AppState, frame: &mut Frame, tree: bool) {
+fn draw_ui(snapshot: &Snapshot, state: &mut AppState, frame: &mut Frame, tree: bool) {
     // Get dynamic terminal size
     let area = frame.area();
 
@@ -1579,24 +3757,48 @@ fn draw_ui(sys: &sysinfo::System, state: &mut AppState, frame: &mut Frame, tree:
         .style(Style::default().bg(BACKGROUND));
     frame.render_widget(background, area);
 
+    // the last line is the status bar
+    let [area, status_line] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ]).areas(area);
+    frame.render_widget(status_bar(state), status_line);
+
+    // the detail view takes the whole screen above the status bar
+    if let Some(view) = state.detail.as_mut() {
+        draw_detail_view(frame, view, area);
+        return;
+    }
+
     // If tree mode is enabled, draw tree and return early
     if tree {
-       let tree_proc = vec![Rc::clone(&state.root_proc)];
-        //let tree_text = tree_display(tree_proc, 0, state.curr_sel,state.sel);
-        let all_lines = tree_display(tree_proc, 0, state.curr_sel,state.sel);
-        let area_height = area.height.saturating_sub(2) as usize; // account for border
-        let total_lines = all_lines.len();
-
-        // Clamp scroll_offset
-        state.scroll_offset = state.scroll_offset.min(total_lines.saturating_sub(area_height));
-
-        // Get visible lines based on scroll offset
-        let visible_lines = &all_lines[state.scroll_offset..(state.scroll_offset + area_height).min(total_lines)];
-        let tree_text = Text::from(visible_lines.to_vec());
-        let tree_widget = Paragraph::new(tree_text)
-            .block(Block::default().title("Process Tree").borders(Borders::ALL))
-            .style(Style::default().bg(BACKGROUND));
-        frame.render_widget(tree_widget, area);
+        let guides = if state.ascii_tree { &ASCII_GUIDES } else { &UNICODE_GUIDES };
+        state.tree_rows = tree_rows(&state.tree_roots, &state.tree_collapsed, snapshot, &state.filter, guides);
+        // keep the cursor on a listed row when its process exits or the filter hides it;
+        // the rows only need searching when the cursor's process moved
+        let cursor_moved = state.tree_rows
+            .get(state.tree_cursor_row)
+            .is_none_or(|row| row.node.borrow().get_key() != state.curr_sel);
+        if cursor_moved {
+            match state.tree_rows.iter().position(|row| row.node.borrow().get_key() == state.curr_sel) {
+                Some(idx) => state.tree_cursor_row = idx,
+                None => state.tree_select(state.tree_index()),
+            }
+        }
+        state.tree_page = area.height.saturating_sub(3).max(1) as usize; // borders and header
+
+        // only the rows in view are built, scrolled just enough to show the cursor
+        let idx = state.tree_index();
+        let page = state.tree_page;
+        if idx < state.scroll_offset {
+            state.scroll_offset = idx;
+        } else if idx >= state.scroll_offset + page {
+            state.scroll_offset = idx + 1 - page;
+        }
+        state.scroll_offset = state.scroll_offset.min(state.tree_rows.len().saturating_sub(page));
+        let mut table_state = TableState::default().with_selected(Some(idx - state.scroll_offset));
+        frame.render_stateful_widget(process_tree(snapshot, state), area, &mut table_state);
+        draw_signal_menu(frame, state, snapshot, area);
         return;
     }
 
@@ -1644,20 +3846,35 @@ fn draw_ui(sys: &sysinfo::System, state: &mut AppState, frame: &mut Frame, tree:
         let thread_section_height = (per_thread.height as f32).floor() as u16;
         state.thread_show_count = ((thread_section_height.saturating_sub(3)) as f64) as usize;
         if state.show_help{
-        frame.render_widget(system_info(&sys), systeminfo);
+        frame.render_widget(system_info(snapshot), systeminfo);
         frame.render_widget(help_panel(), help);
         }
 
         else{ 
-        frame.render_widget(system_info(&sys), top);
-        }
-        frame.render_widget(cpu_info(&sys), cpus);
-        frame.render_widget(get_cpu_graph(&sys, state, cpu_graph), cpu_graph);
-        frame.render_widget(memory_gauges(&sys), mem);
-        frame.render_widget(disk_gauges(&sys), disk);
-        frame.render_widget(process_list(&sys, state), process);
-        frame.render_widget(get_overall_process_data(&sys, state), thread_general);
-        frame.render_widget(thread_info_to_table(state), per_thread);
+        frame.render_widget(system_info(snapshot), top);
+        }
+        frame.render_widget(cpu_info(snapshot), cpus);
+        frame.render_widget(get_cpu_graph(snapshot, state, cpu_graph), cpu_graph);
+        frame.render_widget(memory_gauges(snapshot), mem);
+        frame.render_widget(disk_gauges(snapshot), disk);
+        state.proc_table_area = process;
+        state.thread_table_area = per_thread;
+        match state.group_by {
+            Some(by) if state.group_drill.is_none() => frame.render_widget(group_list(snapshot, state, by), process),
+            _ if state.showing_cgroups() => frame.render_widget(cgroup_list(snapshot, state), process),
+            _ => frame.render_widget(process_list(snapshot, state), process),
+        }
+        frame.render_widget(get_overall_process_data(snapshot, state), thread_general);
+        frame.render_widget(thread_info_to_table(snapshot, state), per_thread);
+
+        if let Some(picker) = &state.column_picker {
+            let height = state.columns.columns().len() as u16 + 4; // borders, header and footer
+            let popup = centered_rect(72, height, area);
+            let mut table_state = TableState::default().with_selected(Some(picker.selected));
+            frame.render_widget(Clear, popup);
+            frame.render_stateful_widget(column_picker(state, picker), popup, &mut table_state);
+        }
+        draw_signal_menu(frame, state, snapshot, area);
         
 
 }
@@ -1699,65 +3916,219 @@ impl ColorExt for Color {
 
 
 fn main() -> io::Result<()> {
+    let args = Args::parse();
+    if args.batch {
+        return run_batch(&args);
+    }
+
+    let config_path = args.config.clone().or_else(Config::default_path);
+    let config = match &config_path {
+        Some(path) => match Config::load(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Invalid config file {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => Config::default(),
+    };
+
+    // Check the requested process before taking over the terminal
+    if let Some(pid) = args.pid {
+        if !Path::new(&format!("/proc/{}", pid)).exists() {
+            eprintln!("No process with PID {}", pid);
+            std::process::exit(1);
+        }
+    }
+
     // Initialize terminal
     let mut terminal = ratatui::init();
+    execute!(io::stdout(), EnableMouseCapture)?;
 
-    let mut sys = System::new_all();
+    let thread_pid = args.pid.unwrap_or(1);
+    let mut collector = Collector::new();
+    collector.set_thread_pid(Some(thread_pid));
+    let mut snapshot = collector.refresh();
     
     
-   let processes_tree: Vec<Rc<RefCell<TreeProc>>> = tree_create();
-   let root_index = find_root(&processes_tree).unwrap();
-   let root_proc = Rc::clone(&processes_tree[root_index]); 
-   let mut niceval;
-
-
-let mut state = AppState::new(15, 15, Rc::clone(&root_proc), root_proc.borrow().get_pid());
+   let tree_roots: Vec<Rc<RefCell<TreeProc>>> = tree_create(&snapshot);
+   let first_root = tree_roots.first().map(|root| root.borrow().get_key()).unwrap_or_default();
+
+
+let mut state = AppState::new(15, 15, tree_roots, first_root);
+    state.columns = ColumnLayout::from_config(&config.columns);
+    state.confirm_signals = config.confirm_signals && !args.no_confirm;
+    state.ascii_tree = config.ascii_tree || args.ascii;
+    state.config = config;
+    state.config_path = config_path;
+    state.proc_sort_mode = args.sort;
+    state.proc_sort_order = args.order.unwrap_or(args.sort.default_order());
+    if !args.filter_pids.is_empty() {
+        let pids: Vec<String> = args.filter_pids.iter().map(|p| p.to_string()).collect();
+        if let Ok(filter) = ProcessFilter::parse(&format!("pid:{}", pids.join(","))) {
+            state.set_filter(filter);
+        }
+    }
+    state.thread_process_pid = thread_pid;
+    if args.pid.is_some() || args.view == View::Thread {
+        state.mode = Mode::Thread;
+    }
 
     
-    let mut tree:bool = false;
-    let mut i = 0; // index into the  tree stack
-    let mut stack: Vec<Rc<RefCell<TreeProc>>> = vec![Rc::clone(&state.root_proc)];
+    let mut tree:bool = args.view == View::Tree;
     
     
     // Give system time to collect baseline metrics
-    sys.refresh_all();
     std::thread::sleep(std::time::Duration::from_millis(500));
 
 
     loop {
+        // PSS and USS are only read while a column shows them or the list is sorted by them
+        let shown = |id: ColumnId| state.columns.visible().any(|c| c.id == id) || state.proc_sort_mode == id;
+        collector.set_proportional_memory(shown(ColumnId::Pss) || shown(ColumnId::Uss));
+        // so are I/O priorities and affinities, a syscall per process each
+        collector.set_io_priorities(shown(ColumnId::IoPriority));
+        collector.set_affinities(shown(ColumnId::Affinity));
+        collector.set_cgroups(state.cgroup_view);
+        // likewise each process's cgroup path, read while something shows or filters by it
+        let owner_used = shown(ColumnId::Owner)
+            || state.group_by == Some(GroupBy::Owner)
+            || state.filter.uses_owner();
+        collector.set_cgroup_paths(state.cgroup_view || owner_used);
+
         // Only refresh if not frozen
         if !state.frozen {
-            sys.refresh_all();
+            // the detail view hides the panels, so only it is refreshed, and only once
+            // per interval rather than after every scroll key
+            if let Some(view) = state.detail.as_mut() {
+                if view.read_at.elapsed() >= Duration::from_secs_f64(args.interval) {
+                    view.refresh();
+                }
+            } else {
+                snapshot = collector.refresh();
+                // the tree follows every refresh, keeping folds, tags and the cursor by process
+                if tree {
+                    state.rebuild_tree(&snapshot);
+                }
+            }
         }
         
-        let total_processes = sys.processes().len();
+        terminal.draw(|frame| draw_ui(&snapshot, & mut state, frame,tree))?;
 
-        terminal.draw(|frame| draw_ui(&sys, & mut state, frame,tree))?;
+        // the process list may be filtered, so navigate over what was just listed
+        let total_processes = if state.showing_groups() {
+            state.cached_groups.as_ref().map_or(0, Vec::len)
+        } else if state.showing_cgroups() {
+            snapshot.cgroups.len()
+        } else {
+            state.cached_pids.as_ref().map_or(snapshot.processes.len(), Vec::len)
+        };
 
         // Handle keyboard input for scrolling and process management
-        if crossterm::event::poll(std::time::Duration::from_millis(750))? {
-            if let Event::Key(key) = crossterm::event::read()? {
+        if crossterm::event::poll(Duration::from_secs_f64(args.interval))? {
+            let event = crossterm::event::read()?;
+            if let Event::Mouse(mouse) = event {
+                let overlay_open = state.search.is_some()
+                    || state.column_picker.is_some()
+                    || state.signal_menu.is_some()
+                    || state.pending_signal.is_some()
+                    || state.nice_prompt.is_some()
+                    || state.sched_dialog.is_some()
+                    || state.io_dialog.is_some()
+                    || state.affinity_editor.is_some()
+                    || state.detail.is_some();
+                if !tree && !overlay_open {
+                    state.handle_mouse(mouse);
+                }
+                continue;
+            }
+            if let Event::Key(key) = event {
+                // the detail view takes every key until it is closed, and quit too while
+                // its filter box is being typed into
+                let editing_filter = state.detail.as_ref().is_some_and(|view| view.editing_filter);
+                if state.detail.is_some() && (editing_filter || key.code != KeyCode::Char('q')) {
+                    state.handle_detail_key(key.code);
+                    continue;
+                }
+                // the search prompt takes every key until it is closed
+                if state.search.is_some() {
+                    state.handle_search_key(key.code);
+                    continue;
+                }
+                if state.column_picker.is_some() {
+                    state.handle_column_picker_key(key);
+                    continue;
+                }
+                if state.signal_menu.is_some() {
+                    state.handle_signal_menu_key(key.code, &snapshot);
+                    continue;
+                }
+                if state.pending_signal.is_some() {
+                    state.handle_confirm_key(key.code, &snapshot);
+                    continue;
+                }
+                if state.nice_prompt.is_some() {
+                    state.handle_nice_prompt_key(key.code, &snapshot);
+                    continue;
+                }
+                if state.sched_dialog.is_some() {
+                    state.handle_sched_dialog_key(key, &snapshot);
+                    continue;
+                }
+                if state.io_dialog.is_some() {
+                    state.handle_io_dialog_key(key.code, &snapshot);
+                    continue;
+                }
+                if state.affinity_editor.is_some() {
+                    state.handle_affinity_editor_key(key.code, &snapshot);
+                    continue;
+                }
+
                 match key.code {
                     // Navigation keys
                     KeyCode::Char('q') => break,
+                    KeyCode::Enter if !tree && state.showing_groups() => state.drill_into_group(),
+                    KeyCode::Enter | KeyCode::Right if !tree && state.showing_cgroups() => state.drill_into_cgroup(&snapshot),
+                    KeyCode::Enter => state.open_detail(&snapshot, tree),
+                    KeyCode::Char('G') if !tree => state.cycle_grouping(),
+                    KeyCode::Esc if !tree && state.group_drill.is_some() => state.leave_group(),
+                    KeyCode::Esc if !tree && state.cgroup_drill.is_some() => state.leave_cgroup(),
+                    KeyCode::Esc if !tree && state.showing_cgroups() => state.toggle_cgroups(),
+                    KeyCode::Char('C') if !tree => {
+                        state.toggle_cgroups();
+                        // walk the hierarchy right away, even while frozen
+                        collector.set_cgroups(state.cgroup_view);
+                        snapshot.cgroups = collector.collect_cgroups();
+                        state.select_marked_cgroup(&snapshot);
+                    }
                     KeyCode::Char('f') => state.toggle_freeze(),
-                    KeyCode::Char('1') => state.change_sort_mode(SortMode::Cpu),
-                    KeyCode::Char('2') => state.change_sort_mode(SortMode::Memory),
-                    KeyCode::Char('3') => state.change_sort_mode(SortMode::Pid),
+                    KeyCode::Char('/') => state.open_search(),
+                    KeyCode::Char('K') | KeyCode::F(9) => state.open_signal_menu(&snapshot, tree),
+                    KeyCode::Char('c') if !tree => state.column_picker = Some(ColumnPicker { selected: 0, error: None }),
+                    KeyCode::Esc => state.set_filter(ProcessFilter::default()),
+                    KeyCode::Char('1') if state.mode == Mode::Proc => state.change_sort_mode(ColumnId::Cpu),
+                    KeyCode::Char('2') if state.mode == Mode::Proc => state.change_sort_mode(ColumnId::Memory),
+                    KeyCode::Char('3') if state.mode == Mode::Proc => state.change_sort_mode(ColumnId::Pid),
+                    KeyCode::Char('1') => state.change_thread_sort_mode(ThreadSortMode::Cpu),
+                    KeyCode::Char('2') => state.change_thread_sort_mode(ThreadSortMode::Priority),
+                    KeyCode::Char('3') => state.change_thread_sort_mode(ThreadSortMode::Tid),
+                    KeyCode::Char('<') => state.cycle_sort(-1),
+                    KeyCode::Char('>') => state.cycle_sort(1),
+                    KeyCode::Char('i') => state.invert_sort(),
                     
                      KeyCode::Left => {
                         if tree{
-                            state.scroll_offset = state.scroll_offset.saturating_sub(1);
+                            state.tree_collapse();
                         }
                         else{
                           state.mode = Mode::Proc;
                         }
                     },
                     
+                    KeyCode::Right if !tree && state.showing_groups() => state.drill_into_group(),
                     KeyCode::Right => {
                         if tree{
-                            
-                            state.scroll_offset += 1;
+                            state.tree_expand();
                         }
                        else {
                          state.mode = Mode::Thread;
@@ -1767,6 +4138,9 @@ let mut state = AppState::new(15, 15, Rc::clone(&root_proc), root_proc.borrow().
                                 state.thread_scroll_position = 0;
                                 state.thread_selected_index = 0;
                                 state.cached_threads = None;
+                                collector.set_thread_pid(Some(pt_pid));
+                                snapshot.thread_pid = Some(pt_pid);
+                                snapshot.threads = collector.collect_threads();
                         }
                        }
                     },
@@ -1774,13 +4148,7 @@ let mut state = AppState::new(15, 15, Rc::clone(&root_proc), root_proc.borrow().
                     // Selection navigation
                     KeyCode::Down => {
                         if tree{
-                            if i + 1 >= stack.len() {
-                                i = 0;
-                            }
-                        else{
-                            i = i + 1;
-                            }
-                        state.curr_sel = stack[i].borrow().get_pid();
+                            state.tree_move(1);
                         }
                         else{
                             state.select_next(total_processes)
@@ -1788,126 +4156,49 @@ let mut state = AppState::new(15, 15, Rc::clone(&root_proc), root_proc.borrow().
                     },
                     KeyCode::Up => {
                         if tree{
-                            if i == 0 {
-                                i = stack.len() - 1 ; 
-                                }
-                            else if i != 0{
-                                i = i - 1;
-                                }
-                            state.curr_sel = stack[i].borrow().get_pid();
+                            state.tree_move(-1);
                         }
                         else{
                             state.select_previous()
                         }
                     },
                   
+                    KeyCode::PageDown if tree => state.tree_move(state.tree_page as isize),
+                    KeyCode::PageUp if tree => state.tree_move(-(state.tree_page as isize)),
                     KeyCode::PageDown => state.page_down(total_processes),
                     KeyCode::PageUp => state.page_up(),
                     
                     // Process management
                     KeyCode::Char('h') => state.toggle_help(),
-                    KeyCode::Char('u') => {
-                        if tree{
-                            for proc in &stack {
-                                if proc.borrow().get_selected() == true{
-                                    if let Err(e) = kill(NixPid::from_raw(proc.borrow().get_pid() as i32), Signal::SIGTERM) {
-                                        eprintln!("Failed to kill PID {}: {}", proc.borrow().get_pid(), e);
-                                    }
-                                }
-                            }
-                        }
-                        else{
-                            if state.mode == Mode::Proc {
-                                if let Err(e) = send_signal_to_selected_process(&sys, &mut state, Signal::SIGTERM) {
-                                    eprintln!("Error sending SIGTERM: {}", e);
-                                }
-                            }
-                            else{
-                                if let Err(e) = send_thread_signal(& mut state, libc::SIGTERM) {
-                                    eprintln!("Error sending SIGTERM: {}", e);
-                                }
-                            }
-                        }
-                    },
-                    KeyCode::Char('k') => {
-                        if state.mode == Mode::Proc {
-                            if let Err(e) = send_signal_to_selected_process(&sys, &mut state, Signal::SIGKILL) {
-                                eprintln!("Error sending SIGKILL: {}", e);
-                            }
-                        }
-                        else{
-                            if let Err(e) = send_thread_signal(& mut state, libc::SIGKILL) {
-                                eprintln!("Error sending SIGKILL: {}", e);
-                            }
-                        }
-                    },                    
-                      KeyCode::Char('s') => {
-                        if tree{
-                            if let Some(proc) = stack.get(i) {
-                                proc.borrow_mut().set_selected(true);
-                            }
-                        }
-                    },
-                    KeyCode::Char('p') => {
-                            if state.mode == Mode::Proc{
-                            if let Err(e) = send_signal_to_selected_process(&sys, &mut state, Signal::SIGSTOP) {
-                            eprintln!("Error sending SIGSTOP: {}", e);
-                            }
-                        }
-                        else{
-                            if let Err(e) = send_thread_signal(& mut state, libc::SIGSTOP) {
-                            eprintln!("Error sending SIGSTOP: {}", e);
-                            }
-                        }
-                    },
-                    
-                    KeyCode::Char('d') => {
-                        if tree{
-                            if let Some(proc) = stack.get(i) {
-                                proc.borrow_mut().set_selected(false);
-                            }
-                        }
-                    },
-                    
-                    KeyCode::Char('r') => {
-                        if state.mode == Mode::Proc {
-                            if let Err(e) = send_signal_to_selected_process(&sys, &mut state, Signal::SIGCONT) {
-                                eprintln!("Error sending SIGCONT: {}", e);
-                            }
-                        }
-                        else{
-                            if let Err(e) = send_thread_signal(& mut state, libc::SIGCONT) {
-                                eprintln!("Error sending SIGCONT: {}", e);
-                            }
-                        }
-                    },
-                    
-                    KeyCode::Char('+') => {
-                        if state.mode == Mode::Proc{
-                            let temp_pid = selected_pid(&state).as_u32();
-                            niceval = unsafe { getpriority(PRIO_PROCESS,temp_pid) };
-                                if niceval < 19{
-                                    niceval += 1;
-                                    let _ = renice_process(selected_pid(&state), niceval);
-                                    }
-                            }
+                    // in the tree, actions apply to every tagged process (or the one under the cursor)
+                    KeyCode::Char('u') => state.request_signal(&snapshot, state.action_target(tree), libc::SIGTERM),
+                    KeyCode::Char('k') => state.request_signal(&snapshot, state.action_target(tree), libc::SIGKILL),
+                    KeyCode::Char('p') => state.request_signal(&snapshot, state.action_target(tree), libc::SIGSTOP),
+                    KeyCode::Char('r') => state.request_signal(&snapshot, state.action_target(tree), libc::SIGCONT),
+                    KeyCode::Char('x') if tree => {
+                        let pids: Vec<u32> = state.tree_target_subtrees().iter().map(|(pid, _)| *pid).collect();
+                        state.request_signal(&snapshot, Some(Target::Processes(pids)), libc::SIGTERM);
                     }
-                    
-                     KeyCode::Char('-') => {
-                        if state.mode == Mode::Proc{
-                            let temp_pid = selected_pid(&state).as_u32();
-                            niceval = unsafe { getpriority(PRIO_PROCESS, temp_pid) };
-                            if niceval > -20{
-                                niceval =  niceval - 1;
-                            }
-                            
-                            let _ = renice_process(selected_pid(&state), niceval);
-                        }
+                    KeyCode::Char('X') if tree => {
+                        let pids: Vec<u32> = state.tree_target_subtrees().iter().map(|(pid, _)| *pid).collect();
+                        state.request_signal(&snapshot, Some(Target::Processes(pids)), libc::SIGKILL);
                     }
-                    
+                    KeyCode::Char('+') if tree || state.mode == Mode::Proc => state.renice(&snapshot, state.action_target(tree), |nice| nice + 1),
+                    KeyCode::Char('-') if tree || state.mode == Mode::Proc => state.renice(&snapshot, state.action_target(tree), |nice| nice - 1),
+                    KeyCode::Char('n') => state.open_nice_prompt(&snapshot, tree),
+                    KeyCode::Char('o') => state.open_sched_dialog(tree),
+                    KeyCode::Char('I') => state.open_io_dialog(tree),
+                    KeyCode::Char('a') => state.open_affinity_editor(&snapshot, tree),
+                    KeyCode::Char('S') if tree => state.tree_tag_subtree(true),
+                    KeyCode::Char('D') if tree => state.tree_tag_subtree(false),
+                    KeyCode::Char('s') if tree => state.tree_tag(true),
+                    KeyCode::Char('d') if tree => state.tree_tag(false),
+
                     KeyCode::Char('t') if key.modifiers.is_empty() => {
                         tree = !tree;
-                        stack_proc(&mut stack, &state.root_proc.clone());
+                        if tree {
+                            state.rebuild_tree(&snapshot);
+                        }
                     },
                     _ => {}
                 }
@@ -1916,6 +4207,7 @@ let mut state = AppState::new(15, 15, Rc::clone(&root_proc), root_proc.borrow().
         }
     }
 
+    execute!(io::stdout(), DisableMouseCapture)?;
     ratatui::restore();
     Ok(())
 }
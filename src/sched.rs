@@ -0,0 +1,250 @@
+//! CPU and I/O scheduling attributes of processes and threads.
+//!
+//! Every function takes a PID or a TID: Linux schedules threads, so a PID
+//! addresses the process's main thread only.
+
+use nix::errno::Errno;
+
+/// CPU scheduling policies from `sched(7)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    Other,
+    Batch,
+    Idle,
+    Fifo,
+    RoundRobin,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 5] = [
+        Policy::Other,
+        Policy::Batch,
+        Policy::Idle,
+        Policy::Fifo,
+        Policy::RoundRobin,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::Other => "SCHED_OTHER",
+            Policy::Batch => "SCHED_BATCH",
+            Policy::Idle => "SCHED_IDLE",
+            Policy::Fifo => "SCHED_FIFO",
+            Policy::RoundRobin => "SCHED_RR",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Policy::Other => "Default time-sharing",
+            Policy::Batch => "CPU-bound, non-interactive",
+            Policy::Idle => "Only when nothing else runs",
+            Policy::Fifo => "Real-time, runs until it yields",
+            Policy::RoundRobin => "Real-time with time slices",
+        }
+    }
+
+    /// FIFO and RR take a real-time priority (1-99); the others take 0.
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, Policy::Fifo | Policy::RoundRobin)
+    }
+
+    fn raw(&self) -> libc::c_int {
+        match self {
+            Policy::Other => libc::SCHED_OTHER,
+            Policy::Batch => libc::SCHED_BATCH,
+            Policy::Idle => libc::SCHED_IDLE,
+            Policy::Fifo => libc::SCHED_FIFO,
+            Policy::RoundRobin => libc::SCHED_RR,
+        }
+    }
+
+    fn from_raw(raw: libc::c_int) -> Option<Self> {
+        // the kernel may or in SCHED_RESET_ON_FORK
+        let raw = raw & !libc::SCHED_RESET_ON_FORK;
+        Policy::ALL.iter().copied().find(|policy| policy.raw() == raw)
+    }
+}
+
+/// The policy and real-time priority of a process or thread.
+pub fn get_scheduler(pid: u32) -> Result<(Policy, i32), Errno> {
+    let raw = Errno::result(unsafe { libc::sched_getscheduler(pid as libc::pid_t) })?;
+    let mut param = libc::sched_param { sched_priority: 0 };
+    Errno::result(unsafe { libc::sched_getparam(pid as libc::pid_t, &mut param) })?;
+    let policy = Policy::from_raw(raw).ok_or(Errno::EINVAL)?;
+    Ok((policy, param.sched_priority))
+}
+
+/// Sets the policy with `sched_setscheduler(2)`; `priority` is ignored for
+/// policies that are not real-time.
+pub fn set_scheduler(pid: u32, policy: Policy, priority: i32) -> Result<(), Errno> {
+    let param = libc::sched_param {
+        sched_priority: if policy.is_realtime() { priority } else { 0 },
+    };
+    Errno::result(unsafe { libc::sched_setscheduler(pid as libc::pid_t, policy.raw(), &param) })
+        .map(drop)
+}
+
+const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+
+/// I/O scheduling classes from `ioprio_set(2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoClass {
+    /// no class set; the kernel derives best-effort from the nice value
+    None,
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+impl IoClass {
+    /// The classes that can be set, highest priority first.
+    pub const SETTABLE: [IoClass; 3] = [IoClass::Realtime, IoClass::BestEffort, IoClass::Idle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IoClass::None => "none",
+            IoClass::Realtime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            IoClass::None => "Follows the nice value",
+            IoClass::Realtime => "First access to the disk",
+            IoClass::BestEffort => "Default, shares the disk by level",
+            IoClass::Idle => "Only when no one else uses the disk",
+        }
+    }
+
+    /// Realtime and best-effort take a level from 0 (highest) to 7.
+    pub fn has_level(&self) -> bool {
+        matches!(self, IoClass::Realtime | IoClass::BestEffort)
+    }
+
+    fn raw(&self) -> i32 {
+        match self {
+            IoClass::None => 0,
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        }
+    }
+}
+
+/// An I/O class and level, as read by `ioprio_get(2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IoPriority {
+    pub class: IoClass,
+    /// 0 (highest) to 7; meaningless for the idle class
+    pub level: i32,
+}
+
+impl IoPriority {
+    /// The priority the kernel applies: a process without a class gets
+    /// best-effort at `(nice + 20) / 5`.
+    pub fn effective(&self, nice: i64) -> IoPriority {
+        match self.class {
+            IoClass::None => IoPriority {
+                class: IoClass::BestEffort,
+                level: ((nice.clamp(-20, 19) + 20) / 5) as i32,
+            },
+            _ => *self,
+        }
+    }
+
+    /// Short form like `ionice` prints: `rt/0`, `be/4`, `idle`.
+    pub fn short(&self) -> String {
+        match self.class {
+            IoClass::None => "none".to_string(),
+            IoClass::Realtime => format!("rt/{}", self.level),
+            IoClass::BestEffort => format!("be/{}", self.level),
+            IoClass::Idle => "idle".to_string(),
+        }
+    }
+}
+
+/// The I/O class and level of a process or thread.
+pub fn get_io_priority(pid: u32) -> Result<IoPriority, Errno> {
+    let raw = Errno::result(unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid as libc::c_long) })?
+        as i32;
+    let class = match raw >> IOPRIO_CLASS_SHIFT {
+        1 => IoClass::Realtime,
+        2 => IoClass::BestEffort,
+        3 => IoClass::Idle,
+        _ => IoClass::None,
+    };
+    Ok(IoPriority {
+        class,
+        level: raw & 0x7,
+    })
+}
+
+/// Sets the I/O class and level with `ioprio_set(2)`; the level is ignored
+/// for the idle class.
+pub fn set_io_priority(pid: u32, class: IoClass, level: i32) -> Result<(), Errno> {
+    let level = if class.has_level() { level.clamp(0, 7) } else { 0 };
+    let raw = (class.raw() << IOPRIO_CLASS_SHIFT) | level;
+    Errno::result(unsafe {
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid as libc::c_long, raw as libc::c_long)
+    })
+    .map(drop)
+}
+
+/// The CPUs a process or thread may run on, in ascending order.
+pub fn get_affinity(pid: u32) -> Result<Vec<usize>, Errno> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    Errno::result(unsafe {
+        libc::sched_getaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &mut set)
+    })?;
+    Ok((0..libc::CPU_SETSIZE as usize)
+        .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+        .collect())
+}
+
+/// Pins a process or thread to `cpus` with `sched_setaffinity(2)`.
+pub fn set_affinity(pid: u32, cpus: &[usize]) -> Result<(), Errno> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for cpu in cpus {
+        if *cpu >= libc::CPU_SETSIZE as usize {
+            return Err(Errno::EINVAL);
+        }
+        unsafe { libc::CPU_SET(*cpu, &mut set) };
+    }
+    Errno::result(unsafe {
+        libc::sched_setaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    })
+    .map(drop)
+}
+
+/// Pins every thread of a process to `cpus`, like `taskset -a`:
+/// `sched_setaffinity(2)` on the PID alone leaves existing threads where they were.
+pub fn set_process_affinity(pid: u32, cpus: &[usize]) -> Result<(), Errno> {
+    let tasks = std::fs::read_dir(format!("/proc/{}/task", pid)).map_err(|_| Errno::ESRCH)?;
+    for tid in tasks.flatten().filter_map(|task| task.file_name().to_str()?.parse::<u32>().ok()) {
+        match set_affinity(tid, cpus) {
+            // the thread exited since the directory was listed
+            Err(Errno::ESRCH) if tid != pid => {}
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+/// Formats sorted CPU numbers as a list with ranges, like `taskset -c`
+/// prints them: `0-3,8`.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = cpus.iter().copied().peekable();
+    while let Some(first) = iter.next() {
+        let mut last = first;
+        while iter.peek() == Some(&(last + 1)) {
+            last = iter.next().unwrap_or(last);
+        }
+        ranges.push(if first == last { first.to_string() } else { format!("{}-{}", first, last) });
+    }
+    ranges.join(",")
+}
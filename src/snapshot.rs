@@ -0,0 +1,183 @@
+//! Point-in-time view of the system produced by [`crate::Collector`].
+
+use crate::cgroup::{Owner, Pressure};
+use crate::sched::IoPriority;
+
+/// Host information shown in the system info panel.
+#[derive(Debug, Clone, Default)]
+pub struct SystemInfo {
+    pub name: String,
+    pub kernel_version: String,
+    pub os_version: String,
+    pub host_name: String,
+    /// seconds since boot
+    pub uptime: u64,
+}
+
+/// Usage of a single logical CPU.
+#[derive(Debug, Clone, Default)]
+pub struct CpuInfo {
+    pub name: String,
+    /// usage in percent since the previous refresh
+    pub usage: f32,
+    /// current frequency in MHz
+    pub frequency: u64,
+}
+
+/// Memory and swap figures in bytes. `used` and `cached` follow btop's
+/// formulas (`total - available` and `cached + sreclaimable - shmem`).
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStats {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub cached: u64,
+    pub free: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+}
+
+/// A mounted disk.
+#[derive(Debug, Clone, Default)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub total_space: u64,
+    pub available_space: u64,
+}
+
+/// One row of the process list.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    pub name: String,
+    /// command line joined with spaces, empty for kernel threads
+    pub cmd: String,
+    pub uid: Option<u32>,
+    pub user: Option<String>,
+    pub nice: i64,
+    pub priority: i64,
+    pub status: String,
+    /// one-letter state from `/proc/<pid>/stat` (R, S, D, Z, T, ...)
+    pub state: char,
+    /// raw CPU usage in percent, like top and htop (not divided by core count)
+    pub cpu_usage: f32,
+    /// resident memory in bytes
+    pub memory: u64,
+    /// virtual memory in bytes
+    pub virtual_memory: u64,
+    /// resident memory backed by files or shared memory, in bytes
+    pub shared_memory: u64,
+    /// controlling terminal, e.g. `pts/3`, or `None` when there is none
+    pub tty: Option<String>,
+    pub session: i32,
+    /// process group ID
+    pub pgrp: i32,
+    /// seconds since the epoch
+    pub start_time: u64,
+    /// accumulated CPU time in milliseconds
+    pub cpu_time: u64,
+    /// bytes read since the process started
+    pub disk_read_bytes: u64,
+    /// bytes written since the process started
+    pub disk_written_bytes: u64,
+    /// bytes per second read since the previous refresh
+    pub disk_read_rate: u64,
+    /// bytes per second written since the previous refresh
+    pub disk_write_rate: u64,
+    /// proportional set size in bytes: shared pages divided among their users;
+    /// `None` unless enabled with `Collector::set_proportional_memory`
+    pub pss: Option<u64>,
+    /// unique set size in bytes: pages no other process maps
+    pub uss: Option<u64>,
+    /// swapped-out memory in bytes, from `/proc/<pid>/status`
+    pub swap: u64,
+    pub thread_count: usize,
+    /// I/O class and level, `None` when `ioprio_get` is not permitted
+    pub io_priority: Option<IoPriority>,
+    /// CPUs the process may run on, `None` when they cannot be read
+    pub affinity: Option<Vec<usize>>,
+    /// CPU the process last ran on, from `/proc/<pid>/stat`
+    pub last_cpu: Option<i32>,
+    /// path in the cgroup v2 hierarchy, e.g. `/user.slice/user-1000.slice/session-2.scope`
+    pub cgroup: Option<String>,
+    /// the systemd unit, container or pod the cgroup path names
+    pub owner: Option<Owner>,
+    /// a thread of another process; sysinfo lists userland threads next to processes
+    pub is_thread: bool,
+}
+
+/// One row of the thread panel.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub state: String,
+    /// CPU usage in percent since the previous sample of this thread
+    pub cpu: f64,
+    pub priority: i64,
+}
+
+/// One row of the cgroup browser.
+#[derive(Debug, Clone, Default)]
+pub struct CgroupInfo {
+    /// path below the cgroup v2 mount point, `/` for the root
+    pub path: String,
+    /// 0 for the root
+    pub depth: usize,
+    /// CPU usage in percent since the previous refresh, like [`ProcessInfo::cpu_usage`]
+    pub cpu_usage: Option<f32>,
+    /// `memory.current` in bytes
+    pub memory_current: Option<u64>,
+    /// `memory.max` in bytes, `None` when unlimited
+    pub memory_max: Option<u64>,
+    /// bytes per second read and written since the previous refresh, from `io.stat`
+    pub io_read_rate: Option<u64>,
+    pub io_write_rate: Option<u64>,
+    /// `pids.current`: tasks in the group and below it
+    pub pids_current: Option<u64>,
+    pub pressure: Pressure,
+    /// processes directly in the group
+    pub procs: usize,
+}
+
+impl CgroupInfo {
+    /// The last component of the path, `/` for the root.
+    pub fn name(&self) -> &str {
+        match self.path.rsplit_once('/') {
+            Some((_, name)) if !name.is_empty() => name,
+            _ => &self.path,
+        }
+    }
+}
+
+/// Everything the panels draw, collected at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    /// milliseconds since the epoch when the snapshot was taken
+    pub timestamp: u64,
+    pub system: SystemInfo,
+    pub cpus: Vec<CpuInfo>,
+    pub global_cpu_usage: f32,
+    pub memory: MemoryStats,
+    pub disks: Vec<DiskInfo>,
+    /// all processes, ordered by PID
+    pub processes: Vec<ProcessInfo>,
+    /// the process whose threads are in `threads`, if any
+    pub thread_pid: Option<u32>,
+    pub threads: Vec<ThreadInfo>,
+    /// the cgroup v2 hierarchy, parents before children; empty unless enabled
+    /// with `Collector::set_cgroups`
+    pub cgroups: Vec<CgroupInfo>,
+}
+
+impl Snapshot {
+    /// Looks up a process by PID.
+    pub fn process(&self, pid: u32) -> Option<&ProcessInfo> {
+        self.processes
+            .binary_search_by_key(&pid, |p| p.pid)
+            .ok()
+            .map(|idx| &self.processes[idx])
+    }
+}
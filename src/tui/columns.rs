@@ -0,0 +1,293 @@
+//! Registry of the process list columns and the user's layout of them.
+
+use clap::ValueEnum;
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+
+const MIN_WIDTH: u16 = 3;
+const MAX_WIDTH: u16 = 80;
+
+/// Every column the process list can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnId {
+    Pid,
+    Name,
+    Nice,
+    Priority,
+    State,
+    Cpu,
+    #[value(alias = "mem")]
+    Memory,
+    StartTime,
+    CpuTime,
+    DiskReadRate,
+    DiskWriteRate,
+    DiskRead,
+    DiskWrite,
+    Threads,
+    User,
+    Uid,
+    Ppid,
+    Command,
+    Virt,
+    Res,
+    Shr,
+    Tty,
+    Session,
+    Pgrp,
+    IoPriority,
+    Affinity,
+    LastCpu,
+    Pss,
+    Uss,
+    Swap,
+    Owner,
+}
+
+impl ColumnId {
+    /// All columns in their default order.
+    pub const ALL: [ColumnId; 31] = [
+        ColumnId::Pid,
+        ColumnId::Name,
+        ColumnId::Nice,
+        ColumnId::Priority,
+        ColumnId::State,
+        ColumnId::Cpu,
+        ColumnId::Memory,
+        ColumnId::StartTime,
+        ColumnId::CpuTime,
+        ColumnId::DiskReadRate,
+        ColumnId::DiskWriteRate,
+        ColumnId::DiskRead,
+        ColumnId::DiskWrite,
+        ColumnId::Threads,
+        ColumnId::User,
+        ColumnId::Uid,
+        ColumnId::Ppid,
+        ColumnId::Command,
+        ColumnId::Virt,
+        ColumnId::Res,
+        ColumnId::Shr,
+        ColumnId::Tty,
+        ColumnId::Session,
+        ColumnId::Pgrp,
+        ColumnId::IoPriority,
+        ColumnId::Affinity,
+        ColumnId::LastCpu,
+        ColumnId::Pss,
+        ColumnId::Uss,
+        ColumnId::Swap,
+        ColumnId::Owner,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            ColumnId::Pid => "PID",
+            ColumnId::Name => "Process Name",
+            ColumnId::Nice => "NI",
+            ColumnId::Priority => "Priority",
+            ColumnId::State => "State",
+            ColumnId::Cpu => "CPU Usage",
+            ColumnId::Memory => "Memory Usage",
+            ColumnId::StartTime => "Start Time",
+            ColumnId::CpuTime => "CPU Time",
+            ColumnId::DiskReadRate => "Disk Read/s",
+            ColumnId::DiskWriteRate => "Disk Write/s",
+            ColumnId::DiskRead => "Total Read",
+            ColumnId::DiskWrite => "Total Write",
+            ColumnId::Threads => "Threads",
+            ColumnId::User => "User",
+            ColumnId::Uid => "UID",
+            ColumnId::Ppid => "PPID",
+            ColumnId::Command => "Command",
+            ColumnId::Virt => "VIRT",
+            ColumnId::Res => "RES",
+            ColumnId::Shr => "SHR",
+            ColumnId::Tty => "TTY",
+            ColumnId::Session => "SID",
+            ColumnId::Pgrp => "PGID",
+            ColumnId::IoPriority => "IO",
+            ColumnId::Affinity => "Affinity",
+            ColumnId::LastCpu => "Last CPU",
+            ColumnId::Pss => "PSS",
+            ColumnId::Uss => "USS",
+            ColumnId::Swap => "Swap",
+            ColumnId::Owner => "Owner",
+        }
+    }
+
+    pub fn default_width(&self) -> u16 {
+        match self {
+            ColumnId::Pid => 8,
+            ColumnId::Name => 20,
+            ColumnId::Nice => 5,
+            ColumnId::Priority => 10,
+            ColumnId::State => 10,
+            ColumnId::Cpu => 12,
+            ColumnId::Memory => 15,
+            ColumnId::StartTime => 15,
+            ColumnId::CpuTime => 10,
+            ColumnId::DiskReadRate => 14,
+            ColumnId::DiskWriteRate => 14,
+            ColumnId::DiskRead => 14,
+            ColumnId::DiskWrite => 14,
+            ColumnId::Threads => 8,
+            ColumnId::User => 10,
+            ColumnId::Uid => 6,
+            ColumnId::Ppid => 8,
+            ColumnId::Command => 30,
+            ColumnId::Virt => 10,
+            ColumnId::Res => 10,
+            ColumnId::Shr => 10,
+            ColumnId::Tty => 7,
+            ColumnId::Session => 8,
+            ColumnId::Pgrp => 8,
+            ColumnId::IoPriority => 6,
+            ColumnId::Affinity => 12,
+            ColumnId::LastCpu => 9,
+            ColumnId::Pss => 10,
+            ColumnId::Uss => 10,
+            ColumnId::Swap => 10,
+            ColumnId::Owner => 24,
+        }
+    }
+
+    /// Whether the column is shown when the config does not mention it.
+    pub fn default_visible(&self) -> bool {
+        matches!(
+            self,
+            ColumnId::Pid
+                | ColumnId::Name
+                | ColumnId::Nice
+                | ColumnId::Priority
+                | ColumnId::State
+                | ColumnId::Cpu
+                | ColumnId::Memory
+                | ColumnId::StartTime
+                | ColumnId::CpuTime
+                | ColumnId::DiskReadRate
+                | ColumnId::DiskWriteRate
+                | ColumnId::Threads
+        )
+    }
+
+    /// The name and command columns take whatever space the others leave.
+    pub fn constraint(&self, width: u16) -> Constraint {
+        match self {
+            ColumnId::Name | ColumnId::Command => Constraint::Min(width),
+            _ => Constraint::Length(width),
+        }
+    }
+}
+
+/// Position-independent settings of one column; the position is its index
+/// in the layout.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColumnConfig {
+    pub id: ColumnId,
+    pub width: u16,
+    pub visible: bool,
+}
+
+impl ColumnConfig {
+    fn default_for(id: ColumnId) -> Self {
+        Self {
+            id,
+            width: id.default_width(),
+            visible: id.default_visible(),
+        }
+    }
+}
+
+/// Order, width and visibility of every column, hidden ones included so
+/// they keep their place when shown again.
+#[derive(Debug, Clone)]
+pub struct ColumnLayout {
+    columns: Vec<ColumnConfig>,
+}
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        Self {
+            columns: ColumnId::ALL.iter().map(|id| ColumnConfig::default_for(*id)).collect(),
+        }
+    }
+}
+
+impl ColumnLayout {
+    /// Builds a layout from the config file, dropping duplicates and
+    /// appending columns the config does not know about yet.
+    pub fn from_config(columns: &[ColumnConfig]) -> Self {
+        if columns.is_empty() {
+            return Self::default();
+        }
+
+        let mut layout: Vec<ColumnConfig> = Vec::new();
+        for column in columns {
+            if !layout.iter().any(|c| c.id == column.id) {
+                layout.push(ColumnConfig {
+                    width: column.width.clamp(MIN_WIDTH, MAX_WIDTH),
+                    ..*column
+                });
+            }
+        }
+        for id in ColumnId::ALL {
+            if !layout.iter().any(|c| c.id == id) {
+                layout.push(ColumnConfig::default_for(id));
+            }
+        }
+        if !layout.iter().any(|c| c.visible) {
+            layout[0].visible = true;
+        }
+
+        Self { columns: layout }
+    }
+
+    pub fn columns(&self) -> &[ColumnConfig] {
+        &self.columns
+    }
+
+    pub fn visible(&self) -> impl Iterator<Item = &ColumnConfig> {
+        self.columns.iter().filter(|c| c.visible)
+    }
+
+    /// Shows or hides a column; the last visible column cannot be hidden.
+    pub fn toggle(&mut self, idx: usize) {
+        let visible_count = self.visible().count();
+        if let Some(column) = self.columns.get_mut(idx) {
+            if !column.visible || visible_count > 1 {
+                column.visible = !column.visible;
+            }
+        }
+    }
+
+    /// Swaps a column with its left neighbour and returns its new index.
+    pub fn move_up(&mut self, idx: usize) -> usize {
+        if idx > 0 && idx < self.columns.len() {
+            self.columns.swap(idx, idx - 1);
+            idx - 1
+        } else {
+            idx
+        }
+    }
+
+    /// Swaps a column with its right neighbour and returns its new index.
+    pub fn move_down(&mut self, idx: usize) -> usize {
+        if idx + 1 < self.columns.len() {
+            self.columns.swap(idx, idx + 1);
+            idx + 1
+        } else {
+            idx
+        }
+    }
+
+    pub fn resize(&mut self, idx: usize, delta: i16) {
+        if let Some(column) = self.columns.get_mut(idx) {
+            column.width = column
+                .width
+                .saturating_add_signed(delta)
+                .clamp(MIN_WIDTH, MAX_WIDTH);
+        }
+    }
+}
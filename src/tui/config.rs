@@ -0,0 +1,66 @@
+//! The config file, `$XDG_CONFIG_HOME/linux-task-manager/config.toml`
+//! (or `~/.config/...` when `XDG_CONFIG_HOME` is not set).
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::columns::ColumnConfig;
+
+/// Settings that persist between runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// process list columns in display order
+    #[serde(default)]
+    pub columns: Vec<ColumnConfig>,
+    /// ask before sending signals that end processes (SIGTERM, SIGKILL, ...)
+    #[serde(default = "default_confirm_signals")]
+    pub confirm_signals: bool,
+    /// draw the tree's branch guides with ASCII characters, like `pstree -A`
+    #[serde(default)]
+    pub ascii_tree: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            confirm_signals: default_confirm_signals(),
+            ascii_tree: false,
+        }
+    }
+}
+
+fn default_confirm_signals() -> bool {
+    true
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("linux-task-manager").join("config.toml"))
+    }
+
+    /// Reads the config, falling back to the defaults when the file does not exist.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
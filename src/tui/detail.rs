@@ -0,0 +1,242 @@
+//! State of the full-screen detail view of one process.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use linux_task_manager::details::{self, MapUsage, MemoryMapping, ProcessDetails};
+use procfs::{ProcError, ProcResult};
+
+use super::sort::SortOrder;
+
+/// Tabs of the detail view, left to right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailTab {
+    Status,
+    Command,
+    Files,
+    Fds,
+    Maps,
+    Limits,
+    Cgroup,
+    Namespaces,
+    Oom,
+    Io,
+}
+
+impl DetailTab {
+    pub const ALL: [DetailTab; 10] = [
+        DetailTab::Status,
+        DetailTab::Command,
+        DetailTab::Files,
+        DetailTab::Fds,
+        DetailTab::Maps,
+        DetailTab::Limits,
+        DetailTab::Cgroup,
+        DetailTab::Namespaces,
+        DetailTab::Oom,
+        DetailTab::Io,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DetailTab::Status => "Status",
+            DetailTab::Command => "Command & Env",
+            DetailTab::Files => "Cwd/Exe/Root",
+            DetailTab::Fds => "Open Files",
+            DetailTab::Maps => "Memory Map",
+            DetailTab::Limits => "Limits",
+            DetailTab::Cgroup => "Cgroup",
+            DetailTab::Namespaces => "Namespaces",
+            DetailTab::Oom => "OOM",
+            DetailTab::Io => "I/O",
+        }
+    }
+}
+
+/// Sort keys of the memory map tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapSortKey {
+    Address,
+    Size,
+    Rss,
+    Pss,
+    PrivateDirty,
+    Swap,
+    Path,
+}
+
+impl MapSortKey {
+    pub const ALL: [MapSortKey; 7] = [
+        MapSortKey::Address,
+        MapSortKey::Size,
+        MapSortKey::Rss,
+        MapSortKey::Pss,
+        MapSortKey::PrivateDirty,
+        MapSortKey::Swap,
+        MapSortKey::Path,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MapSortKey::Address => "Address",
+            MapSortKey::Size => "Size",
+            MapSortKey::Rss => "RSS",
+            MapSortKey::Pss => "PSS",
+            MapSortKey::PrivateDirty => "Private Dirty",
+            MapSortKey::Swap => "Swap",
+            MapSortKey::Path => "Path",
+        }
+    }
+
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            MapSortKey::Address | MapSortKey::Path => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        }
+    }
+
+    fn compare(&self, a: &MapRow, b: &MapRow) -> Ordering {
+        match self {
+            MapSortKey::Address => a.start.cmp(&b.start),
+            MapSortKey::Size => a.size.cmp(&b.size),
+            MapSortKey::Rss => a.usage.rss.cmp(&b.usage.rss),
+            MapSortKey::Pss => a.usage.pss.cmp(&b.usage.pss),
+            MapSortKey::PrivateDirty => a.usage.private_dirty.cmp(&b.usage.private_dirty),
+            MapSortKey::Swap => a.usage.swap.cmp(&b.usage.swap),
+            MapSortKey::Path => a.path.cmp(&b.path),
+        }
+    }
+}
+
+/// A row of the memory map tab: one mapping, or every mapping of one file
+/// when grouped.
+pub struct MapRow {
+    /// lowest start address, so grouped rows sort by address too
+    pub start: u64,
+    /// the address range, or the number of mappings when grouped
+    pub range: String,
+    pub perms: String,
+    pub path: String,
+    pub size: u64,
+    pub usage: MapUsage,
+}
+
+/// The detail view: which process, which tab and how far it is scrolled.
+pub struct DetailView {
+    pub pid: u32,
+    pub name: String,
+    pub tab: DetailTab,
+    pub scroll: u16,
+    /// case-insensitive filter of the open files tab
+    pub filter: String,
+    /// whether keys go to the filter box
+    pub editing_filter: bool,
+    /// `smaps`, read only while the memory map tab is shown
+    pub maps: Option<ProcResult<Vec<MemoryMapping>>>,
+    pub map_sort: MapSortKey,
+    pub map_order: SortOrder,
+    /// one row per backing file instead of per mapping
+    pub map_grouped: bool,
+    /// `Err` once the process has exited; the view stays open to say so
+    pub details: ProcResult<ProcessDetails>,
+    /// when `details` was last read
+    pub read_at: Instant,
+}
+
+impl DetailView {
+    pub fn new(pid: u32, name: String) -> Self {
+        Self {
+            pid,
+            name,
+            tab: DetailTab::Status,
+            scroll: 0,
+            filter: String::new(),
+            editing_filter: false,
+            maps: None,
+            map_sort: MapSortKey::Rss,
+            map_order: MapSortKey::Rss.default_order(),
+            map_grouped: false,
+            details: ProcessDetails::read(pid),
+            read_at: Instant::now(),
+        }
+    }
+
+    /// Re-reads the details, e.g. for fresh context switch and I/O counters.
+    /// Once the process is gone it stays gone, even if its PID is reused.
+    pub fn refresh(&mut self) {
+        self.read_at = Instant::now();
+        let Ok(current) = &self.details else {
+            return;
+        };
+        let start_time = current.start_time;
+        self.details = ProcessDetails::read(self.pid).and_then(|details| {
+            if details.start_time == start_time {
+                Ok(details)
+            } else {
+                Err(ProcError::NotFound(None))
+            }
+        });
+        self.read_maps();
+    }
+
+    pub fn set_tab(&mut self, tab: DetailTab) {
+        if tab != self.tab {
+            self.tab = tab;
+            self.scroll = 0;
+            self.read_maps();
+        }
+    }
+
+    fn read_maps(&mut self) {
+        if self.tab == DetailTab::Maps && self.details.is_ok() {
+            self.maps = Some(details::memory_maps(self.pid));
+        }
+    }
+
+    /// The rows of the memory map tab, grouped and sorted as chosen.
+    pub fn map_rows(&self) -> Vec<MapRow> {
+        let Some(Ok(maps)) = &self.maps else {
+            return Vec::new();
+        };
+
+        let mut rows: Vec<MapRow> = if self.map_grouped {
+            let mut groups: HashMap<&str, (usize, MapRow)> = HashMap::new();
+            for map in maps {
+                let (count, row) = groups.entry(map.path.as_str()).or_insert_with(|| (0, MapRow {
+                    start: map.start,
+                    range: String::new(),
+                    perms: String::new(),
+                    path: map.path.clone(),
+                    size: 0,
+                    usage: MapUsage::default(),
+                }));
+                *count += 1;
+                row.start = row.start.min(map.start);
+                row.size += map.end - map.start;
+                row.usage.add(&map.usage);
+            }
+            groups
+                .into_values()
+                .map(|(count, row)| MapRow {
+                    range: format!("{} mapping{}", count, if count == 1 { "" } else { "s" }),
+                    ..row
+                })
+                .collect()
+        } else {
+            maps.iter()
+                .map(|map| MapRow {
+                    start: map.start,
+                    range: format!("{:012x}-{:012x}", map.start, map.end),
+                    perms: map.perms.clone(),
+                    path: map.path.clone(),
+                    size: map.end - map.start,
+                    usage: map.usage,
+                })
+                .collect()
+        };
+
+        rows.sort_by(|a, b| self.map_order.apply(self.map_sort.compare(a, b)).then(a.start.cmp(&b.start)));
+        rows
+    }
+}
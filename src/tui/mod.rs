@@ -0,0 +1,7 @@
+//! Terminal UI building blocks that are not part of the data collection library.
+
+pub mod columns;
+pub mod config;
+pub mod detail;
+pub mod signals;
+pub mod sort;
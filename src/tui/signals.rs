@@ -0,0 +1,99 @@
+//! Signals offered by the signal menu: everything `nix` knows about plus the
+//! real-time signals, which it has no names for.
+
+use libc::c_int;
+use nix::sys::signal::Signal;
+
+/// One entry of the signal menu.
+#[derive(Debug, Clone)]
+pub struct SignalEntry {
+    pub number: c_int,
+    pub name: String,
+}
+
+/// Standard signals in numeric order, followed by `SIGRTMIN..=SIGRTMAX`.
+pub fn all() -> Vec<SignalEntry> {
+    let mut entries: Vec<SignalEntry> = Signal::iterator()
+        .map(|signal| SignalEntry {
+            number: signal as c_int,
+            name: signal.as_str().to_string(),
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry.number);
+    entries.extend((libc::SIGRTMIN()..=libc::SIGRTMAX()).map(|number| SignalEntry {
+        number,
+        name: name(number),
+    }));
+    entries
+}
+
+/// The signal's name, with real-time signals numbered the way `kill -l` does
+/// (`SIGRTMIN+3`, `SIGRTMAX-2`).
+pub fn name(number: c_int) -> String {
+    if let Ok(signal) = Signal::try_from(number) {
+        return signal.as_str().to_string();
+    }
+    let (min, max) = (libc::SIGRTMIN(), libc::SIGRTMAX());
+    match number {
+        n if n == min => "SIGRTMIN".to_string(),
+        n if n == max => "SIGRTMAX".to_string(),
+        n if n > min && n - min <= (max - min) / 2 => format!("SIGRTMIN+{}", n - min),
+        n if n > min && n < max => format!("SIGRTMAX-{}", max - n),
+        n => format!("signal {}", n),
+    }
+}
+
+/// Signals whose default action ends the process; these are confirmed before
+/// being sent unless confirmation is turned off.
+pub fn is_destructive(number: c_int) -> bool {
+    matches!(
+        Signal::try_from(number),
+        Ok(Signal::SIGHUP
+            | Signal::SIGINT
+            | Signal::SIGQUIT
+            | Signal::SIGABRT
+            | Signal::SIGKILL
+            | Signal::SIGTERM)
+    )
+}
+
+/// What the signal usually means to the process receiving it.
+pub fn description(number: c_int) -> &'static str {
+    let Ok(signal) = Signal::try_from(number) else {
+        return "Real-time signal";
+    };
+    match signal {
+        Signal::SIGHUP => "Hangup, daemons reload",
+        Signal::SIGINT => "Interrupt (Ctrl+C)",
+        Signal::SIGQUIT => "Quit and dump core",
+        Signal::SIGILL => "Illegal instruction",
+        Signal::SIGTRAP => "Trace/breakpoint trap",
+        Signal::SIGABRT => "Abort",
+        Signal::SIGBUS => "Bus error",
+        Signal::SIGFPE => "Arithmetic error",
+        Signal::SIGKILL => "Kill, cannot be caught",
+        Signal::SIGUSR1 => "User-defined 1",
+        Signal::SIGSEGV => "Segmentation fault",
+        Signal::SIGUSR2 => "User-defined 2",
+        Signal::SIGPIPE => "Broken pipe",
+        Signal::SIGALRM => "Timer expired",
+        Signal::SIGTERM => "Terminate",
+        Signal::SIGSTKFLT => "Stack fault",
+        Signal::SIGCHLD => "Child stopped or exited",
+        Signal::SIGCONT => "Continue if stopped",
+        Signal::SIGSTOP => "Stop, cannot be caught",
+        Signal::SIGTSTP => "Stop from terminal (Ctrl+Z)",
+        Signal::SIGTTIN => "Background read from terminal",
+        Signal::SIGTTOU => "Background write to terminal",
+        Signal::SIGURG => "Urgent socket data",
+        Signal::SIGXCPU => "CPU time limit exceeded",
+        Signal::SIGXFSZ => "File size limit exceeded",
+        Signal::SIGVTALRM => "Virtual timer expired",
+        Signal::SIGPROF => "Profiling timer expired",
+        Signal::SIGWINCH => "Window resized",
+        Signal::SIGIO => "I/O possible",
+        Signal::SIGPWR => "Power failure",
+        Signal::SIGSYS => "Bad system call",
+        _ => "",
+    }
+}
@@ -0,0 +1,181 @@
+//! Sort keys for the process list (any column) and the thread panel.
+
+use std::cmp::Ordering;
+
+use clap::ValueEnum;
+use linux_task_manager::aggregate::ProcessGroup;
+use linux_task_manager::{ProcessInfo, ThreadInfo};
+
+use super::columns::ColumnId;
+
+#[derive(PartialEq, Debug, Clone, Copy, ValueEnum)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "▲",
+            SortOrder::Desc => "▼",
+        }
+    }
+
+    pub fn reversed(&self) -> Self {
+        match self {
+            SortOrder::Asc => SortOrder::Desc,
+            SortOrder::Desc => SortOrder::Asc,
+        }
+    }
+
+    pub fn apply(&self, ordering: Ordering) -> Ordering {
+        match self {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+impl ColumnId {
+    /// The direction a column sorts in until the user inverts it: usage
+    /// figures biggest first, names and IDs smallest first.
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            ColumnId::Cpu
+            | ColumnId::Memory
+            | ColumnId::CpuTime
+            | ColumnId::DiskReadRate
+            | ColumnId::DiskWriteRate
+            | ColumnId::DiskRead
+            | ColumnId::DiskWrite
+            | ColumnId::Threads
+            | ColumnId::Virt
+            | ColumnId::Res
+            | ColumnId::Shr
+            | ColumnId::Pss
+            | ColumnId::Uss
+            | ColumnId::Swap => SortOrder::Desc,
+            _ => SortOrder::Asc,
+        }
+    }
+
+    /// Ascending comparison of two processes by this column.
+    pub fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        match self {
+            ColumnId::Pid => a.pid.cmp(&b.pid),
+            ColumnId::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ColumnId::Nice => a.nice.cmp(&b.nice),
+            ColumnId::Priority => a.priority.cmp(&b.priority),
+            ColumnId::State => a.status.cmp(&b.status),
+            ColumnId::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            ColumnId::Memory | ColumnId::Res => a.memory.cmp(&b.memory),
+            ColumnId::StartTime => a.start_time.cmp(&b.start_time),
+            ColumnId::CpuTime => a.cpu_time.cmp(&b.cpu_time),
+            ColumnId::DiskReadRate => a.disk_read_rate.cmp(&b.disk_read_rate),
+            ColumnId::DiskWriteRate => a.disk_write_rate.cmp(&b.disk_write_rate),
+            ColumnId::DiskRead => a.disk_read_bytes.cmp(&b.disk_read_bytes),
+            ColumnId::DiskWrite => a.disk_written_bytes.cmp(&b.disk_written_bytes),
+            ColumnId::Threads => a.thread_count.cmp(&b.thread_count),
+            ColumnId::User => a.user.cmp(&b.user).then(a.uid.cmp(&b.uid)),
+            ColumnId::Uid => a.uid.cmp(&b.uid),
+            ColumnId::Ppid => a.ppid.cmp(&b.ppid),
+            ColumnId::Command => a.cmd.cmp(&b.cmd),
+            ColumnId::Virt => a.virtual_memory.cmp(&b.virtual_memory),
+            ColumnId::Shr => a.shared_memory.cmp(&b.shared_memory),
+            ColumnId::Tty => a.tty.cmp(&b.tty),
+            ColumnId::Session => a.session.cmp(&b.session),
+            ColumnId::Pgrp => a.pgrp.cmp(&b.pgrp),
+            ColumnId::IoPriority => {
+                let a = a.io_priority.map(|io| io.effective(a.nice));
+                let b = b.io_priority.map(|io| io.effective(b.nice));
+                a.cmp(&b)
+            }
+            // pinned processes first
+            ColumnId::Affinity => {
+                let count = |p: &ProcessInfo| p.affinity.as_ref().map(Vec::len);
+                count(a).cmp(&count(b)).then_with(|| a.affinity.cmp(&b.affinity))
+            }
+            ColumnId::LastCpu => a.last_cpu.cmp(&b.last_cpu),
+            ColumnId::Pss => a.pss.cmp(&b.pss),
+            ColumnId::Uss => a.uss.cmp(&b.uss),
+            ColumnId::Swap => a.swap.cmp(&b.swap),
+            ColumnId::Owner => {
+                let label = |p: &ProcessInfo| p.owner.as_ref().map(|owner| owner.label());
+                label(a).cmp(&label(b))
+            }
+        }
+    }
+
+    /// Ascending comparison of two process groups by the sum this column
+    /// stands for; columns without one sort by process count.
+    pub fn compare_groups(&self, a: &ProcessGroup, b: &ProcessGroup) -> Ordering {
+        match self {
+            ColumnId::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            ColumnId::Memory | ColumnId::Res => a.memory.cmp(&b.memory),
+            ColumnId::Threads => a.threads.cmp(&b.threads),
+            ColumnId::DiskReadRate => a.disk_read_rate.cmp(&b.disk_read_rate),
+            ColumnId::DiskWriteRate => a.disk_write_rate.cmp(&b.disk_write_rate),
+            ColumnId::Name | ColumnId::Command | ColumnId::User | ColumnId::Uid | ColumnId::Owner => a.key.cmp(&b.key),
+            _ => a.processes.cmp(&b.processes),
+        }
+    }
+}
+
+/// Sort keys of the thread panel.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ThreadSortMode {
+    Tid,
+    Name,
+    State,
+    Cpu,
+    Priority,
+}
+
+impl ThreadSortMode {
+    /// The thread panel's columns, left to right.
+    pub const ALL: [ThreadSortMode; 5] = [
+        ThreadSortMode::Tid,
+        ThreadSortMode::Name,
+        ThreadSortMode::State,
+        ThreadSortMode::Cpu,
+        ThreadSortMode::Priority,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThreadSortMode::Tid => "TID",
+            ThreadSortMode::Name => "NAME",
+            ThreadSortMode::State => "STATE",
+            ThreadSortMode::Cpu => "CPU",
+            ThreadSortMode::Priority => "PRIO",
+        }
+    }
+
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            ThreadSortMode::Cpu => SortOrder::Desc,
+            _ => SortOrder::Asc,
+        }
+    }
+
+    /// Ascending comparison of two threads by this key.
+    pub fn compare(&self, a: &ThreadInfo, b: &ThreadInfo) -> Ordering {
+        match self {
+            ThreadSortMode::Tid => a.tid.cmp(&b.tid),
+            ThreadSortMode::Name => a.name.cmp(&b.name),
+            ThreadSortMode::State => a.state.cmp(&b.state),
+            ThreadSortMode::Cpu => a.cpu.total_cmp(&b.cpu),
+            ThreadSortMode::Priority => a.priority.cmp(&b.priority),
+        }
+    }
+}
+
+/// The entry `step` places after `current` in `list`, wrapping around.
+pub fn cycle<T: PartialEq + Copy>(list: &[T], current: T, step: isize) -> T {
+    let len = list.len() as isize;
+    match list.iter().position(|item| *item == current) {
+        Some(idx) => list[(idx as isize + step).rem_euclid(len) as usize],
+        None => list[0],
+    }
+}